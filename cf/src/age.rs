@@ -0,0 +1,64 @@
+//! Human-readable ages, like `2d` for `--since` or `3h ago` in listings.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const UNITS: &[(char, i64)] = &[
+    ('w', 7 * 24 * 3600),
+    ('d', 24 * 3600),
+    ('h', 3600),
+    ('m', 60),
+    ('s', 1),
+];
+
+/// Current Unix time in seconds.
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Parses an age like `90s`, `30m`, `12h`, `2d` or `1w` into seconds.
+pub fn parse(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    let invalid = || format!("Invalid age: {}. Use e.g. 30m, 12h, 2d or 1w", text);
+    let unit = text.chars().last().ok_or_else(invalid)?;
+    let (_, secs) = UNITS.iter().find(|(u, _)| *u == unit).ok_or_else(invalid)?;
+    let count: i64 = text[..text.len() - 1].parse().map_err(|_| invalid())?;
+    if count < 0 {
+        return Err(invalid());
+    }
+    count.checked_mul(*secs).ok_or_else(invalid)
+}
+
+/// Formats an age in seconds with its largest unit, e.g. `4h` for 4.5 hours.
+pub fn format(secs: i64) -> String {
+    let secs = secs.max(0);
+    UNITS
+        .iter()
+        .find(|(_, unit)| secs >= *unit)
+        .map_or("0s".to_string(), |(u, unit)| {
+            format!("{}{}", secs / unit, u)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(parse("2d"), Ok(2 * 24 * 3600));
+        assert_eq!(parse("30m"), Ok(1800));
+        assert_eq!(parse("1w"), Ok(7 * 24 * 3600));
+        assert!(parse("2").is_err());
+        assert!(parse("d").is_err());
+        assert!(parse("2y").is_err());
+        assert!(parse("9999999999999999w").is_err());
+
+        assert_eq!(format(0), "0s");
+        assert_eq!(format(59), "59s");
+        assert_eq!(format(4 * 3600 + 5), "4h");
+        assert_eq!(format(10 * 24 * 3600), "1w");
+        assert_eq!(format(-5), "0s");
+    }
+}
@@ -0,0 +1,611 @@
+//! Typed client for the Codeforces API (https://codeforces.com/apiHelp).
+
+use crate::cache::{self, Cache};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The API allows about one call per two seconds.
+const MIN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Retries after `Call limit exceeded` or 503, waiting twice as long each
+/// time.
+const RETRIES: u32 = 3;
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request couldn't be sent or the response couldn't be read
+    Network(String),
+    /// The response wasn't the JSON we expected
+    Parse(String),
+    /// The API answered with `status: FAILED`
+    Failed(String),
+    /// The server answered with an HTTP error and no API response
+    Status(u16),
+    /// The method needs an API key and none is configured
+    NoCredentials,
+    /// `--offline` and the response isn't cached
+    Offline(String),
+}
+
+impl ApiError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::Failed(comment) => comment.contains("Call limit exceeded"),
+            ApiError::Status(status) => *status == 503,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::Network(e) => write!(f, "Network error: {}", e),
+            ApiError::Parse(e) => write!(f, "Failed to parse response: {}", e),
+            ApiError::Failed(comment) => write!(f, "API error: {}", comment),
+            ApiError::Status(status) => write!(f, "HTTP error: {}", status),
+            ApiError::Offline(url) => write!(f, "Not cached (offline): {}", url),
+            ApiError::NoCredentials => {
+                write!(f, "This needs an API key. Run: cf login --api-key")
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    status: String,
+    comment: Option<String>,
+    result: Option<T>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub handle: String,
+    pub rating: Option<i64>,
+    pub max_rating: Option<i64>,
+    pub rank: Option<String>,
+    pub max_rank: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Problem {
+    /// Missing for problems that only live in a problemset, like acm.sgu
+    pub contest_id: Option<u64>,
+    pub problemset_name: Option<String>,
+    pub index: String,
+    pub name: String,
+    pub rating: Option<u32>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Problem {
+    /// Name as used for solution files: `1900A`, `102001K` or `sgu100`.
+    pub fn short_name(&self) -> String {
+        if self.problemset_name.as_deref() == Some("acmsguru") {
+            format!("sgu{}", self.index)
+        } else {
+            format!("{}{}", self.contest_id.unwrap_or(0), self.index)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Submission {
+    pub id: u64,
+    pub contest_id: Option<u64>,
+    pub creation_time_seconds: i64,
+    pub problem: Problem,
+    pub programming_language: String,
+    /// Missing while the submission is in the queue
+    pub verdict: Option<String>,
+    pub passed_test_count: u32,
+    pub time_consumed_millis: u64,
+    pub memory_consumed_bytes: u64,
+}
+
+impl Submission {
+    /// Whether judging is over and the verdict won't change.
+    pub fn is_judged(&self) -> bool {
+        !matches!(self.verdict.as_deref(), None | Some("TESTING"))
+    }
+
+    /// The verdict as Codeforces shows it, e.g. `Wrong answer on test 5`.
+    pub fn verdict_text(&self) -> String {
+        // The failing test is the one after the last passed
+        let test = self.passed_test_count + 1;
+        match self.verdict.as_deref() {
+            None => "In queue".to_string(),
+            Some("TESTING") => format!("Running on test {}", test),
+            Some("OK") => "Accepted".to_string(),
+            Some("WRONG_ANSWER") => format!("Wrong answer on test {}", test),
+            Some("TIME_LIMIT_EXCEEDED") => format!("Time limit exceeded on test {}", test),
+            Some("MEMORY_LIMIT_EXCEEDED") => format!("Memory limit exceeded on test {}", test),
+            Some("RUNTIME_ERROR") => format!("Runtime error on test {}", test),
+            Some("IDLENESS_LIMIT_EXCEEDED") => format!("Idleness limit exceeded on test {}", test),
+            Some("PRESENTATION_ERROR") => format!("Presentation error on test {}", test),
+            Some("COMPILATION_ERROR") => "Compilation error".to_string(),
+            Some("PARTIAL") => "Partial result".to_string(),
+            Some("CHALLENGED") => "Hacked".to_string(),
+            Some(other) => {
+                let text = other.to_lowercase().replace('_', " ");
+                let mut chars = text.chars();
+                chars.next().map_or(String::new(), |c| {
+                    c.to_uppercase().chain(chars).collect::<String>()
+                })
+            }
+        }
+    }
+
+    /// Short verdict for listings, e.g. `WA on test 5`.
+    pub fn verdict_short(&self) -> String {
+        let test = self.passed_test_count + 1;
+        let short = match self.verdict.as_deref() {
+            Some("WRONG_ANSWER") => "WA",
+            Some("TIME_LIMIT_EXCEEDED") => "TLE",
+            Some("MEMORY_LIMIT_EXCEEDED") => "MLE",
+            Some("RUNTIME_ERROR") => "RE",
+            Some("IDLENESS_LIMIT_EXCEEDED") => "ILE",
+            Some("PRESENTATION_ERROR") => "PE",
+            _ => return self.verdict_text(),
+        };
+        format!("{} on test {}", short, test)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingChange {
+    pub contest_id: u64,
+    pub contest_name: String,
+    pub rank: u32,
+    pub old_rating: i64,
+    pub new_rating: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Member {
+    pub handle: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Party {
+    pub members: Vec<Member>,
+    pub team_name: Option<String>,
+}
+
+impl Party {
+    /// Team name, else the members' handles.
+    pub fn name(&self) -> String {
+        self.team_name.clone().unwrap_or_else(|| {
+            let handles: Vec<&str> = self.members.iter().map(|m| m.handle.as_str()).collect();
+            handles.join(", ")
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RanklistRow {
+    pub party: Party,
+    pub rank: u32,
+    pub points: f64,
+    pub penalty: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Standings {
+    pub contest: Contest,
+    pub rows: Vec<RanklistRow>,
+}
+
+/// An API key and secret, used to sign requests.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub key: String,
+    pub secret: String,
+}
+
+pub struct Client {
+    http: reqwest::blocking::Client,
+    /// Site URL such as `https://codeforces.com`, without a trailing slash
+    site: String,
+    credentials: Option<Credentials>,
+    cache: Option<Cache>,
+    offline: bool,
+}
+
+impl Client {
+    pub fn new(site: &str, timeout: Duration) -> Client {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to build HTTP client");
+        Client {
+            http,
+            site: site.to_string(),
+            credentials: None,
+            cache: Cache::open(),
+            offline: false,
+        }
+    }
+
+    /// Serves responses only from the cache, however old.
+    pub fn with_offline(mut self, offline: bool) -> Client {
+        self.offline = offline;
+        self
+    }
+
+    /// Looks `url` up in the cache: fresh entries only, unless offline.
+    /// Offline, a miss is an error.
+    fn cached(&self, kind: &str, url: &str) -> Result<Option<String>, ApiError> {
+        let max_age = (!self.offline).then(|| cache::ttl(kind));
+        let body = self.cache.as_ref().and_then(|c| c.get(url, max_age));
+        if body.is_none() && self.offline {
+            return Err(ApiError::Offline(url.to_string()));
+        }
+        Ok(body)
+    }
+
+    fn store(&self, kind: &str, url: &str, body: &str) {
+        if let Some(cache) = &self.cache {
+            cache.put(kind, url, body);
+        }
+    }
+
+    /// Signs every request, which makes private contests and gyms visible
+    /// and enables methods such as `user.friends`.
+    pub fn with_credentials(mut self, credentials: Option<Credentials>) -> Client {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Calls an API method and unwraps its `result`. Served from the cache
+    /// while fresh, otherwise throttled to the API's rate limit and retried
+    /// when the limit is hit anyway.
+    fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, ApiError> {
+        // Keyed by the unsigned parameters, plus the key for private data
+        let mut sorted = params.to_vec();
+        sorted.sort();
+        let mut query: Vec<String> = sorted.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        if let Some(credentials) = &self.credentials {
+            query.push(format!("apiKey={}", credentials.key));
+        }
+        let url = format!("{}/api/{}?{}", self.site, method, query.join("&"));
+        if let Some(body) = self.cached(method, &url)? {
+            return parse_response(&body);
+        }
+
+        let mut delay = MIN_INTERVAL;
+        for attempt in 0.. {
+            throttle();
+            match self.send(method, params) {
+                Ok(body) => {
+                    let result = parse_response(&body);
+                    if result.is_ok() {
+                        self.store(method, &url, &body);
+                    }
+                    return result;
+                }
+                Err(e) if attempt < RETRIES && e.is_retryable() => {
+                    eprintln!("{}, retrying in {}s...", e, delay.as_secs());
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!("the last attempt returns")
+    }
+
+    /// Sends a request, returning the body of a successful API response.
+    fn send(&self, method: &str, params: &[(&str, String)]) -> Result<String, ApiError> {
+        let mut params: Vec<(String, String)> = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        if let Some(credentials) = &self.credentials {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            params.push(("apiKey".to_string(), credentials.key.clone()));
+            params.push(("time".to_string(), time.to_string()));
+            let rand = format!("{:06}", fastrand::u32(0..1_000_000));
+            let sig = sign(&rand, method, &params, &credentials.secret);
+            params.push(("apiSig".to_string(), sig));
+        }
+
+        let response = self
+            .http
+            .get(format!("{}/api/{}", self.site, method))
+            .query(&params)
+            .send()
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        // Failed calls come with 400 and an API response explaining why
+        match parse_response::<IgnoredAny>(&text) {
+            Ok(_) => Ok(text),
+            Err(ApiError::Parse(_)) if !status.is_success() => {
+                Err(ApiError::Status(status.as_u16()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetches an HTML page of the site, such as a problem statement.
+    pub fn page(&self, url: &str) -> Result<String, ApiError> {
+        if let Some(body) = self.cached("page", url)? {
+            return Ok(body);
+        }
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ApiError::Status(status.as_u16()));
+        }
+        let body = response
+            .text()
+            .map_err(|e| ApiError::Network(e.to_string()))?;
+        self.store("page", url, &body);
+        Ok(body)
+    }
+
+    pub fn user_info(&self, handles: &[&str]) -> Result<Vec<User>, ApiError> {
+        self.call("user.info", &[("handles", handles.join(";"))])
+    }
+
+    /// Submissions of a user, newest first.
+    pub fn user_status(
+        &self,
+        handle: &str,
+        from: usize,
+        count: usize,
+    ) -> Result<Vec<Submission>, ApiError> {
+        self.call(
+            "user.status",
+            &[
+                ("handle", handle.to_string()),
+                ("from", from.to_string()),
+                ("count", count.to_string()),
+            ],
+        )
+    }
+
+    /// All submissions of a user, newest first.
+    pub fn user_status_all(&self, handle: &str) -> Result<Vec<Submission>, ApiError> {
+        self.call("user.status", &[("handle", handle.to_string())])
+    }
+
+    /// Handles of the authenticated user's friends.
+    pub fn user_friends(&self, only_online: bool) -> Result<Vec<String>, ApiError> {
+        if self.credentials.is_none() {
+            return Err(ApiError::NoCredentials);
+        }
+        self.call("user.friends", &[("onlyOnline", only_online.to_string())])
+    }
+
+    pub fn user_rating(&self, handle: &str) -> Result<Vec<RatingChange>, ApiError> {
+        self.call("user.rating", &[("handle", handle.to_string())])
+    }
+
+    /// Submissions of a user in one contest, newest first. This is the only
+    /// way to see submissions to a specific gym contest.
+    pub fn contest_status(
+        &self,
+        contest_id: u64,
+        handle: &str,
+        from: usize,
+        count: usize,
+    ) -> Result<Vec<Submission>, ApiError> {
+        self.call(
+            "contest.status",
+            &[
+                ("contestId", contest_id.to_string()),
+                ("handle", handle.to_string()),
+                ("from", from.to_string()),
+                ("count", count.to_string()),
+            ],
+        )
+    }
+
+    /// All problems of the problemset, with ratings and tags.
+    pub fn problemset_problems(&self) -> Result<Vec<Problem>, ApiError> {
+        #[derive(Deserialize)]
+        struct Problemset {
+            problems: Vec<Problem>,
+        }
+        let problemset: Problemset = self.call("problemset.problems", &[])?;
+        Ok(problemset.problems)
+    }
+
+    /// Top rows of a contest's standings. Private contests and gyms need
+    /// credentials.
+    pub fn contest_standings(
+        &self,
+        contest_id: u64,
+        from: usize,
+        count: usize,
+    ) -> Result<Standings, ApiError> {
+        self.call(
+            "contest.standings",
+            &[
+                ("contestId", contest_id.to_string()),
+                ("from", from.to_string()),
+                ("count", count.to_string()),
+            ],
+        )
+    }
+}
+
+/// File holding the time of the last API call, shared by all invocations.
+fn last_call_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("cf").join("last-api-call"))
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis())
+}
+
+/// Waits until `MIN_INTERVAL` has passed since the last call, then records
+/// this one.
+fn throttle() {
+    let Some(path) = last_call_path() else {
+        return;
+    };
+    let last = fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| s.trim().parse::<u128>().ok());
+    if let Some(last) = last {
+        let elapsed = now_millis().saturating_sub(last);
+        let wait = MIN_INTERVAL.as_millis().saturating_sub(elapsed);
+        if wait > 0 {
+            std::thread::sleep(Duration::from_millis(wait as u64));
+        }
+    }
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(&path, now_millis().to_string());
+}
+
+/// `apiSig`: `rand` followed by the hex SHA-512 of
+/// `rand/methodName?param1=value1&param2=value2#secret`, with the
+/// parameters sorted by name, then value.
+fn sign(rand: &str, method: &str, params: &[(String, String)], secret: &str) -> String {
+    let mut sorted = params.to_vec();
+    sorted.sort();
+    let query: Vec<String> = sorted.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    let hash = Sha512::digest(format!(
+        "{}/{}?{}#{}",
+        rand,
+        method,
+        query.join("&"),
+        secret
+    ));
+    let hex: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}", rand, hex)
+}
+
+fn parse_response<T: DeserializeOwned>(text: &str) -> Result<T, ApiError> {
+    let response: Response<T> =
+        serde_json::from_str(text).map_err(|e| ApiError::Parse(e.to_string()))?;
+    if response.status != "OK" {
+        return Err(ApiError::Failed(
+            response.comment.unwrap_or_else(|| response.status.clone()),
+        ));
+    }
+    response
+        .result
+        .ok_or_else(|| ApiError::Parse("missing result".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verdict_text() {
+        let mut sub: Submission = serde_json::from_str(
+            r#"{"id":1,"creationTimeSeconds":0,"problem":{"index":"A","name":"X"},
+                "programmingLanguage":"Python 3","passedTestCount":13,
+                "timeConsumedMillis":0,"memoryConsumedBytes":0}"#,
+        )
+        .unwrap();
+        assert_eq!(sub.verdict_text(), "In queue");
+        assert!(!sub.is_judged());
+        sub.verdict = Some("TESTING".to_string());
+        assert_eq!(sub.verdict_text(), "Running on test 14");
+        assert!(!sub.is_judged());
+        sub.verdict = Some("WRONG_ANSWER".to_string());
+        assert_eq!(sub.verdict_text(), "Wrong answer on test 14");
+        assert_eq!(sub.verdict_short(), "WA on test 14");
+        assert!(sub.is_judged());
+        sub.verdict = Some("SECURITY_VIOLATED".to_string());
+        assert_eq!(sub.verdict_text(), "Security violated");
+    }
+
+    #[test]
+    fn test_parse_failed() {
+        let err = parse_response::<Vec<User>>(
+            r#"{"status":"FAILED","comment":"handles: User with handle nobody123 not found"}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ApiError::Failed(c) if c.contains("not found")));
+        assert!(matches!(
+            parse_response::<Vec<User>>("<html>"),
+            Err(ApiError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_retryable() {
+        assert!(ApiError::Failed("Call limit exceeded".to_string()).is_retryable());
+        assert!(ApiError::Status(503).is_retryable());
+        assert!(!ApiError::Failed("handle: not found".to_string()).is_retryable());
+        assert!(!ApiError::Network("timed out".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_sign() {
+        // Example from https://codeforces.com/apiHelp
+        let params = [
+            ("contestId".to_string(), "566".to_string()),
+            ("apiKey".to_string(), "xxx".to_string()),
+            ("time".to_string(), "1433106000".to_string()),
+        ];
+        assert_eq!(
+            sign("123456", "contest.hacks", &params, "yyy"),
+            "123456\
+             7a1e4c31f0eb7b110af0110b680290d87ba3e9fb876cf6e97d7977364de0f90c\
+             5c0443bfd18c0bc85036ae07a8f5b9801a9a3b2395b07b8901d000a27f40cd82"
+        );
+    }
+
+    #[test]
+    fn test_parse_submissions() {
+        let submissions: Vec<Submission> = parse_response(
+            r#"{"status":"OK","result":[
+                {"id":1,"contestId":1900,"creationTimeSeconds":1700000000,
+                 "problem":{"contestId":1900,"index":"A","name":"Cover in Water","type":"PROGRAMMING","rating":800,"tags":["greedy"]},
+                 "author":{"members":[{"handle":"x"}]},"programmingLanguage":"Python 3",
+                 "verdict":"OK","testset":"TESTS","passedTestCount":12,"timeConsumedMillis":46,"memoryConsumedBytes":0},
+                {"id":2,"creationTimeSeconds":1700000001,
+                 "problem":{"problemsetName":"acmsguru","index":"100","name":"A+B","type":"PROGRAMMING","tags":[]},
+                 "programmingLanguage":"GNU C++17","passedTestCount":0,"timeConsumedMillis":0,"memoryConsumedBytes":0}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(submissions[0].problem.short_name(), "1900A");
+        assert_eq!(submissions[0].problem.rating, Some(800));
+        assert_eq!(submissions[1].problem.short_name(), "sgu100");
+        assert_eq!(submissions[1].verdict, None);
+    }
+}
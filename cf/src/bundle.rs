@@ -0,0 +1,488 @@
+//! Expands references to the local Rust library crate into a single
+//! submittable source file.
+//!
+//! A solution that does `use mylib::math::gcd;` gets the `math` module (and
+//! every library module it depends on) appended as `mod mylib { ... }`, with
+//! `#[cfg(test)]` items stripped.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct Library {
+    /// Crate name as used in `use` paths (hyphens replaced by underscores).
+    pub name: String,
+    /// Source of `lib.rs` with module declarations and tests removed.
+    root: String,
+    modules: Vec<Module>,
+}
+
+struct Module {
+    name: String,
+    visibility: String,
+    /// Fully expanded body, nested file modules already inlined.
+    body: String,
+}
+
+impl Library {
+    pub fn load(dir: &Path) -> Result<Library, String> {
+        let manifest = fs::read_to_string(dir.join("Cargo.toml"))
+            .map_err(|e| format!("Failed to read {}: {}", dir.join("Cargo.toml").display(), e))?;
+        let name = manifest
+            .lines()
+            .map(str::trim)
+            .find_map(|line| {
+                let rest = line.strip_prefix("name")?.trim_start().strip_prefix('=')?;
+                Some(rest.trim().trim_matches('"').replace('-', "_"))
+            })
+            .ok_or_else(|| format!("No package name in {}", dir.join("Cargo.toml").display()))?;
+
+        let src = dir.join("src");
+        let lib_rs = src.join("lib.rs");
+        let source = fs::read_to_string(&lib_rs)
+            .map_err(|e| format!("Failed to read {}: {}", lib_rs.display(), e))?;
+        let (root, decls) = split_mod_decls(&strip_cfg_test(&source));
+
+        let mut modules = Vec::new();
+        for (visibility, module) in decls {
+            let body = expand_module(&src, &module)?;
+            modules.push(Module {
+                name: module,
+                visibility,
+                body,
+            });
+        }
+
+        Ok(Library {
+            name,
+            root,
+            modules,
+        })
+    }
+
+    /// Bundles `source` with the library modules it uses. Returns the source
+    /// unchanged if it doesn't reference the library.
+    pub fn bundle(&self, source: &str) -> String {
+        let used = referenced_modules(source, &self.name);
+        if used.is_empty() {
+            return source.to_string();
+        }
+
+        // Pull in modules that the used ones depend on via `crate::`/`super::`
+        let mut needed: BTreeSet<String> = BTreeSet::new();
+        let mut queue: Vec<String> = used.into_iter().collect();
+        while let Some(name) = queue.pop() {
+            let Some(module) = self.modules.iter().find(|m| m.name == name) else {
+                continue;
+            };
+            if !needed.insert(name) {
+                continue;
+            }
+            for prefix in ["crate", "super"] {
+                queue.extend(referenced_modules(&module.body, prefix));
+            }
+        }
+        for prefix in ["crate", "super"] {
+            needed.extend(
+                referenced_modules(&self.root, prefix)
+                    .into_iter()
+                    .filter(|n| self.modules.iter().any(|m| &m.name == n)),
+            );
+        }
+
+        let mut out = String::new();
+        for line in rewrite_paths(source, &self.name, &format!("crate::{}", self.name)).lines() {
+            if line.trim() == format!("extern crate {};", self.name) {
+                continue;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+
+        out.push_str("\n#[allow(dead_code, unused_imports, unused_macros)]\n");
+        out.push_str(&format!("mod {} {{\n", self.name));
+        let crate_path = format!("crate::{}", self.name);
+        if !self.root.trim().is_empty() {
+            out.push_str(&indent(&rewrite_paths(&self.root, "crate", &crate_path)));
+            out.push('\n');
+        }
+        for module in self.modules.iter().filter(|m| needed.contains(&m.name)) {
+            out.push_str(&format!(
+                "    {}mod {} {{\n",
+                module.visibility, module.name
+            ));
+            out.push_str(&indent(&indent(&rewrite_paths(
+                module.body.trim_end(),
+                "crate",
+                &crate_path,
+            ))));
+            out.push_str("    }\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Bundles the solution at `path` with the library crate in `lib_dir` into
+/// `{out_dir}/{name}.rs` and returns the path of the file to compile or
+/// submit. Solutions that don't use the library are returned as is.
+pub fn bundle_solution(
+    path: &Path,
+    name: &str,
+    lib_dir: &Path,
+    out_dir: &Path,
+) -> Result<PathBuf, String> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    if !lib_dir.join("Cargo.toml").exists() {
+        return Ok(path.to_path_buf());
+    }
+    let library = Library::load(lib_dir)?;
+    if referenced_modules(&source, &library.name).is_empty() {
+        return Ok(path.to_path_buf());
+    }
+
+    fs::create_dir_all(out_dir)
+        .map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+    let out = out_dir.join(format!("{}.rs", name));
+    fs::write(&out, library.bundle(&source))
+        .map_err(|e| format!("Failed to write {}: {}", out.display(), e))?;
+    Ok(out)
+}
+
+fn expand_module(dir: &Path, name: &str) -> Result<String, String> {
+    let candidates = [
+        dir.join(format!("{}.rs", name)),
+        dir.join(name).join("mod.rs"),
+    ];
+    let file = candidates
+        .iter()
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("Module {} not found in {}", name, dir.display()))?;
+    let source = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+
+    let (mut body, decls) = split_mod_decls(&strip_cfg_test(&source));
+    let sub_dir = dir.join(name);
+    for (visibility, sub) in decls {
+        let sub_body = expand_module(&sub_dir, &sub)?;
+        body.push_str(&format!(
+            "\n{}mod {} {{\n{}}}\n",
+            visibility,
+            sub,
+            indent(&sub_body)
+        ));
+    }
+    Ok(body)
+}
+
+/// Separates `mod foo;` declarations from the rest of the source. Returns the
+/// remaining source and `(visibility, name)` for each declaration.
+fn split_mod_decls(source: &str) -> (String, Vec<(String, String)>) {
+    let mut rest = String::new();
+    let mut decls = Vec::new();
+    for line in source.lines() {
+        match parse_mod_decl(line.trim()) {
+            Some(decl) => decls.push(decl),
+            None => {
+                rest.push_str(line);
+                rest.push('\n');
+            }
+        }
+    }
+    (rest, decls)
+}
+
+fn parse_mod_decl(line: &str) -> Option<(String, String)> {
+    let decl = line.strip_suffix(';')?;
+    let (visibility, decl) = match decl.find("mod ") {
+        Some(i) if decl[..i].trim().is_empty() || decl[..i].trim().starts_with("pub") => {
+            (decl[..i].to_string(), &decl[i + 4..])
+        }
+        _ => return None,
+    };
+    let name = decl.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some((visibility, name.to_string()))
+}
+
+/// Removes every item annotated with `#[cfg(test)]`.
+pub fn strip_cfg_test(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(pos) = find_code(rest, "#[cfg(test)]") {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos + "#[cfg(test)]".len()..];
+        rest = skip_item(rest);
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Skips a single item: further attributes, then everything up to a `;` or a
+/// balanced `{ ... }` block, whichever comes first.
+fn skip_item(source: &str) -> &str {
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    let mut depth = 0usize;
+    let mut brackets = 0usize;
+    while i < bytes.len() {
+        if let Some(next) = skip_literal_or_comment(source, i) {
+            i = next;
+            continue;
+        }
+        match bytes[i] {
+            b'[' => brackets += 1,
+            b']' => brackets = brackets.saturating_sub(1),
+            b'{' if brackets == 0 => depth += 1,
+            b'}' if brackets == 0 => {
+                if depth == 0 {
+                    return &source[i..];
+                }
+                depth -= 1;
+                if depth == 0 {
+                    return source[i + 1..]
+                        .trim_start_matches([' ', '\t'])
+                        .trim_start_matches('\n');
+                }
+            }
+            b';' if depth == 0 && brackets == 0 => {
+                return source[i + 1..]
+                    .trim_start_matches([' ', '\t'])
+                    .trim_start_matches('\n');
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    ""
+}
+
+/// Returns the index after the string, char literal or comment starting at
+/// `i`, or `None` if there isn't one.
+fn skip_literal_or_comment(source: &str, i: usize) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let rest = &source[i..];
+    if rest.starts_with("//") {
+        return Some(rest.find('\n').map_or(source.len(), |n| i + n));
+    }
+    if let Some(comment) = rest.strip_prefix("/*") {
+        return Some(comment.find("*/").map_or(source.len(), |n| i + n + 4));
+    }
+    if rest.starts_with("r\"") || rest.starts_with("r#") {
+        let hashes = rest[1..].chars().take_while(|&c| c == '#').count();
+        if rest[1 + hashes..].starts_with('"') {
+            let close = format!("\"{}", "#".repeat(hashes));
+            let start = 2 + hashes;
+            return Some(
+                rest[start..]
+                    .find(&close)
+                    .map_or(source.len(), |n| i + start + n + close.len()),
+            );
+        }
+        return None;
+    }
+    match bytes[i] {
+        b'"' => {
+            let mut j = i + 1;
+            while j < bytes.len() {
+                match bytes[j] {
+                    b'\\' => j += 2,
+                    b'"' => return Some(j + 1),
+                    _ => j += 1,
+                }
+            }
+            Some(bytes.len())
+        }
+        b'\'' => {
+            // Char literal ('a', '\n', '\u{..}') as opposed to a lifetime
+            if bytes.get(i + 1) == Some(&b'\\') {
+                rest[2..].find('\'').map(|n| i + n + 3)
+            } else {
+                let c = rest[1..].chars().next()?;
+                let end = 1 + c.len_utf8();
+                (rest.as_bytes().get(end) == Some(&b'\'')).then_some(i + end + 1)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Finds `needle` outside of strings and comments.
+fn find_code(source: &str, needle: &str) -> Option<usize> {
+    let mut i = 0;
+    while i < source.len() {
+        if let Some(next) = skip_literal_or_comment(source, i) {
+            i = next;
+            continue;
+        }
+        if source[i..].starts_with(needle) {
+            return Some(i);
+        }
+        i += source[i..].chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Positions where `prefix::` starts a path (not preceded by an identifier
+/// character or `::`).
+fn path_starts(source: &str, prefix: &str) -> Vec<usize> {
+    let needle = format!("{}::", prefix);
+    let bytes = source.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < source.len() {
+        if let Some(next) = skip_literal_or_comment(source, i) {
+            i = next;
+            continue;
+        }
+        if source[i..].starts_with(&needle) {
+            let standalone = i == 0 || !(is_ident_byte(bytes[i - 1]) || bytes[i - 1] == b':');
+            if standalone {
+                found.push(i);
+            }
+            i += needle.len();
+            continue;
+        }
+        i += source[i..].chars().next().map_or(1, char::len_utf8);
+    }
+    found
+}
+
+/// Names of the top-level modules referenced as `prefix::module`, including
+/// `prefix::{a, b::c}` groups.
+fn referenced_modules(source: &str, prefix: &str) -> BTreeSet<String> {
+    let mut modules = BTreeSet::new();
+    for start in path_starts(source, prefix) {
+        let rest = source[start + prefix.len() + 2..].trim_start();
+        if let Some(group) = rest.strip_prefix('{') {
+            let mut depth = 0;
+            let mut segment_start = true;
+            for (i, c) in group.char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth == 0 => break,
+                    '}' => depth -= 1,
+                    ',' if depth == 0 => segment_start = true,
+                    c if segment_start && (c.is_alphanumeric() || c == '_') => {
+                        let ident: String = group[i..]
+                            .chars()
+                            .take_while(|c| c.is_alphanumeric() || *c == '_')
+                            .collect();
+                        modules.insert(ident);
+                        segment_start = false;
+                    }
+                    _ => {}
+                }
+            }
+        } else {
+            let ident: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                .collect();
+            if !ident.is_empty() {
+                modules.insert(ident);
+            }
+        }
+    }
+    modules
+}
+
+/// Replaces paths starting with `from::` by `to::`.
+fn rewrite_paths(source: &str, from: &str, to: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut last = 0;
+    for start in path_starts(source, from) {
+        out.push_str(&source[last..start]);
+        out.push_str(to);
+        last = start + from.len();
+    }
+    out.push_str(&source[last..]);
+    out
+}
+
+fn indent(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::from("\n")
+            } else {
+                format!("    {}\n", line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_cfg_test() {
+        let src =
+            "fn a() {}\n#[cfg(test)]\nmod tests {\n    fn b() { let s = \"}\"; }\n}\nfn c() {}\n";
+        assert_eq!(strip_cfg_test(src), "fn a() {}\nfn c() {}\n");
+        assert_eq!(
+            strip_cfg_test("#[cfg(test)]\nmod tests;\nfn a() {}\n"),
+            "fn a() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_referenced_modules() {
+        let src = "use mylib::math::gcd;\nuse mylib::{io::Scanner, graph};\n// mylib::ignored\nlet x = notmylib::y;";
+        let mods: Vec<String> = referenced_modules(src, "mylib").into_iter().collect();
+        assert_eq!(mods, ["graph", "io", "math"]);
+    }
+
+    #[test]
+    fn test_rewrite_paths() {
+        assert_eq!(
+            rewrite_paths(
+                "use crate::a::b; x::crate::c; \"crate::d\"",
+                "crate",
+                "crate::lib"
+            ),
+            "use crate::lib::a::b; x::crate::c; \"crate::d\""
+        );
+    }
+
+    #[test]
+    fn test_bundle() {
+        let dir = std::env::temp_dir().join(format!("cf-bundle-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"my-lib\"\n").unwrap();
+        fs::write(
+            dir.join("src/lib.rs"),
+            "pub mod math;\npub mod num;\npub mod unused;\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("src/math.rs"),
+            "use crate::num::Int;\npub fn gcd(a: Int, b: Int) -> Int { if b == 0 { a } else { gcd(b, a % b) } }\n\
+             #[cfg(test)]\nmod tests {\n    #[test]\n    fn t() {}\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src/num.rs"), "pub type Int = i64;\n").unwrap();
+        fs::write(dir.join("src/unused.rs"), "pub fn unused() {}\n").unwrap();
+
+        let library = Library::load(&dir).unwrap();
+        let bundled = library.bundle("use my_lib::math::gcd;\nfn main() {}\n");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(bundled.starts_with("use crate::my_lib::math::gcd;\n"));
+        assert!(bundled.contains("pub mod math {"));
+        assert!(bundled.contains("pub mod num {"));
+        assert!(bundled.contains("use crate::my_lib::num::Int;"));
+        assert!(!bundled.contains("fn unused"));
+        assert!(!bundled.contains("cfg(test)"));
+    }
+}
@@ -0,0 +1,175 @@
+//! On-disk cache of HTTP responses, shared by API calls and page scraping.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+/// How long a response of an API method (or `page` for HTML pages) stays
+/// fresh. Responses are cached regardless, so `--offline` can serve them.
+pub fn ttl(kind: &str) -> Duration {
+    Duration::from_secs(match kind {
+        // Statements and problem lists rarely change
+        "page" => 30 * DAY,
+        "problemset.problems" => DAY,
+        "user.info" | "user.rating" | "user.friends" => 10 * MINUTE,
+        // Submissions must be live for watch, standings during a contest
+        _ => 0,
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    kind: String,
+    url: String,
+    fetched_at: u64,
+    body: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Stats {
+    /// Entry count and total size by kind
+    pub kinds: BTreeMap<String, (usize, u64)>,
+    /// Entries whose TTL has passed
+    pub expired: usize,
+}
+
+pub struct Cache {
+    dir: PathBuf,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Cache {
+        Cache { dir }
+    }
+
+    /// The cache in the user's cache directory.
+    pub fn open() -> Option<Cache> {
+        Some(Cache::new(dirs::cache_dir()?.join("cf").join("http")))
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        let hash = Sha256::digest(url.as_bytes());
+        let name: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    fn entry(&self, url: &str) -> Option<Entry> {
+        let text = fs::read_to_string(self.path(url)).ok()?;
+        serde_json::from_str::<Entry>(&text)
+            .ok()
+            .filter(|e| e.url == url)
+    }
+
+    /// The cached body for `url` if it's younger than `max_age`, or of any
+    /// age with `None`.
+    pub fn get(&self, url: &str, max_age: Option<Duration>) -> Option<String> {
+        let entry = self.entry(url)?;
+        let age = now().saturating_sub(entry.fetched_at);
+        match max_age {
+            Some(max_age) if age >= max_age.as_secs() => None,
+            _ => Some(entry.body),
+        }
+    }
+
+    pub fn put(&self, kind: &str, url: &str, body: &str) {
+        let entry = Entry {
+            kind: kind.to_string(),
+            url: url.to_string(),
+            fetched_at: now(),
+            body: body.to_string(),
+        };
+        let _ = fs::create_dir_all(&self.dir);
+        if let Ok(text) = serde_json::to_string(&entry) {
+            let _ = fs::write(self.path(url), text);
+        }
+    }
+
+    fn entries(&self) -> Vec<(PathBuf, Entry)> {
+        let Ok(dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        dir.flatten()
+            .filter_map(|e| {
+                let text = fs::read_to_string(e.path()).ok()?;
+                Some((e.path(), serde_json::from_str(&text).ok()?))
+            })
+            .collect()
+    }
+
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        for (path, entry) in self.entries() {
+            let size = fs::metadata(&path).map_or(0, |m| m.len());
+            let kind = stats.kinds.entry(entry.kind.clone()).or_default();
+            kind.0 += 1;
+            kind.1 += size;
+            if now().saturating_sub(entry.fetched_at) >= ttl(&entry.kind).as_secs() {
+                stats.expired += 1;
+            }
+        }
+        stats
+    }
+
+    /// Removes all entries, or only expired ones, returning how many.
+    pub fn clear(&self, expired_only: bool) -> usize {
+        let mut removed = 0;
+        for (path, entry) in self.entries() {
+            let expired = now().saturating_sub(entry.fetched_at) >= ttl(&entry.kind).as_secs();
+            if (!expired_only || expired) && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_put() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().join("http"));
+        let url = "https://codeforces.com/api/user.info?handles=tourist";
+        assert_eq!(cache.get(url, None), None);
+
+        cache.put("user.info", url, "{}");
+        assert_eq!(cache.get(url, None).as_deref(), Some("{}"));
+        assert_eq!(
+            cache.get(url, Some(Duration::from_secs(60))).as_deref(),
+            Some("{}")
+        );
+        assert_eq!(cache.get(url, Some(Duration::ZERO)), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.kinds["user.info"].0, 1);
+        assert_eq!(stats.expired, 0);
+
+        cache.put(
+            "user.status",
+            "https://codeforces.com/api/user.status",
+            "[]",
+        );
+        assert_eq!(cache.clear(true), 1);
+        assert_eq!(cache.clear(false), 1);
+        assert!(cache.stats().kinds.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+//! Comparison of a solution's output with the expected answer.
+
+use std::fmt;
+use std::str::FromStr;
+
+const DEFAULT_EPS: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Checker {
+    /// Whole output equal after trimming surrounding whitespace
+    #[default]
+    Trim,
+    /// Equal line by line, ignoring trailing whitespace and blank lines at
+    /// the end
+    Lines,
+    /// Equal as whitespace-separated tokens
+    Tokens,
+    /// Tokens, with numbers equal within an absolute or relative error
+    Float(f64),
+}
+
+impl Checker {
+    pub fn check(&self, expected: &str, actual: &str) -> bool {
+        match self {
+            Checker::Trim => expected.trim() == actual.trim(),
+            Checker::Lines => {
+                let lines = |s: &str| -> Vec<String> {
+                    let mut lines: Vec<String> =
+                        s.lines().map(|l| l.trim_end().to_string()).collect();
+                    while lines.last().is_some_and(|l| l.is_empty()) {
+                        lines.pop();
+                    }
+                    lines
+                };
+                lines(expected) == lines(actual)
+            }
+            Checker::Tokens => expected.split_whitespace().eq(actual.split_whitespace()),
+            Checker::Float(eps) => {
+                let expected: Vec<&str> = expected.split_whitespace().collect();
+                let actual: Vec<&str> = actual.split_whitespace().collect();
+                expected.len() == actual.len()
+                    && expected.iter().zip(&actual).all(|(e, a)| {
+                        match (e.parse::<f64>(), a.parse::<f64>()) {
+                            (Ok(e), Ok(a)) => {
+                                let diff = (e - a).abs();
+                                diff <= *eps || diff <= eps * e.abs()
+                            }
+                            _ => e == a,
+                        }
+                    })
+            }
+        }
+    }
+}
+
+impl FromStr for Checker {
+    type Err = String;
+
+    /// Accepts `trim`, `lines`, `tokens`, `float` and `float:1e-9`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None => match s {
+                "trim" => Ok(Checker::Trim),
+                "lines" => Ok(Checker::Lines),
+                "tokens" => Ok(Checker::Tokens),
+                "float" => Ok(Checker::Float(DEFAULT_EPS)),
+                _ => Err(format!(
+                    "Unknown checker: {}. Use: trim, lines, tokens, float[:eps]",
+                    s
+                )),
+            },
+            Some(("float", eps)) => eps
+                .parse::<f64>()
+                .ok()
+                .filter(|eps| *eps >= 0.0)
+                .map(Checker::Float)
+                .ok_or_else(|| format!("Invalid float checker tolerance: {}", eps)),
+            Some(_) => Err(format!(
+                "Unknown checker: {}. Use: trim, lines, tokens, float[:eps]",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Checker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Checker::Trim => write!(f, "trim"),
+            Checker::Lines => write!(f, "lines"),
+            Checker::Tokens => write!(f, "tokens"),
+            Checker::Float(eps) => write!(f, "float:{}", eps),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkers() {
+        assert!(Checker::Trim.check("1 2\n3\n", "1 2\n3"));
+        assert!(!Checker::Trim.check("1 2\n3", "1  2\n3"));
+        assert!(Checker::Lines.check("1 2\n3\n", "1 2   \n3\n\n"));
+        assert!(!Checker::Lines.check("1 2\n3", "1 2 3"));
+        assert!(Checker::Tokens.check("1 2\n3", "1  2 3\n"));
+        assert!(!Checker::Tokens.check("1 2 3", "1 2"));
+        assert!(Checker::Float(1e-6).check("0.3333333 YES", "0.33333331 YES"));
+        assert!(!Checker::Float(1e-6).check("0.5", "0.51"));
+        assert!(Checker::Float(1e-6).check("1000000000", "1000000001"));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("tokens".parse::<Checker>(), Ok(Checker::Tokens));
+        assert_eq!("float".parse::<Checker>(), Ok(Checker::Float(1e-6)));
+        assert_eq!("float:1e-9".parse::<Checker>(), Ok(Checker::Float(1e-9)));
+        assert!("float:abc".parse::<Checker>().is_err());
+        assert!("exact".parse::<Checker>().is_err());
+    }
+}
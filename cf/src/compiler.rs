@@ -0,0 +1,148 @@
+//! Codeforces compilers (`programTypeId` values) for each language.
+
+/// A compiler choice on the submit form.
+pub struct Compiler {
+    /// Name used in settings, e.g. `clang++20`
+    pub name: &'static str,
+    /// Extension of the language it compiles
+    pub ext: &'static str,
+    pub id: u32,
+    /// Name as shown on Codeforces
+    pub description: &'static str,
+}
+
+/// Known compilers; the first one for each language is the default.
+pub const COMPILERS: &[Compiler] = &[
+    Compiler {
+        name: "g++20",
+        ext: "cpp",
+        id: 89,
+        description: "GNU G++20 (64 bit, winlibs)",
+    },
+    Compiler {
+        name: "g++23",
+        ext: "cpp",
+        id: 91,
+        description: "GNU G++23 (64 bit, msys2)",
+    },
+    Compiler {
+        name: "g++17",
+        ext: "cpp",
+        id: 54,
+        description: "GNU G++17",
+    },
+    Compiler {
+        name: "clang++20",
+        ext: "cpp",
+        id: 80,
+        description: "Clang++20 Diagnostics",
+    },
+    Compiler {
+        name: "clang++17",
+        ext: "cpp",
+        id: 52,
+        description: "Clang++17 Diagnostics",
+    },
+    Compiler {
+        name: "python3",
+        ext: "py",
+        id: 31,
+        description: "Python 3",
+    },
+    Compiler {
+        name: "pypy3",
+        ext: "py",
+        id: 70,
+        description: "PyPy 3.10 (64 bit)",
+    },
+    Compiler {
+        name: "pypy3-32",
+        ext: "py",
+        id: 41,
+        description: "PyPy 3 (32 bit)",
+    },
+    Compiler {
+        name: "rust2021",
+        ext: "rs",
+        id: 75,
+        description: "Rust 2021",
+    },
+    Compiler {
+        name: "kotlin1.9",
+        ext: "kt",
+        id: 88,
+        description: "Kotlin 1.9",
+    },
+    Compiler {
+        name: "kotlin1.7",
+        ext: "kt",
+        id: 83,
+        description: "Kotlin 1.7",
+    },
+    Compiler {
+        name: "ghc",
+        ext: "hs",
+        id: 12,
+        description: "Haskell GHC",
+    },
+];
+
+/// The `programTypeId` for a language (by extension), from a setting
+/// `value` that is a compiler name or a raw ID, else the default compiler.
+pub fn program_type_id(ext: &str, value: Option<&str>) -> Result<u32, String> {
+    match value {
+        Some(value) => parse(ext, value),
+        None => COMPILERS
+            .iter()
+            .find(|c| c.ext == ext)
+            .map(|c| c.id)
+            .ok_or_else(|| format!("No compiler known for .{} files", ext)),
+    }
+}
+
+/// Parses a compiler name of the language, or any numeric ID for compilers
+/// not in the table.
+pub fn parse(ext: &str, value: &str) -> Result<u32, String> {
+    if let Ok(id) = value.parse::<u32>() {
+        return Ok(id);
+    }
+    COMPILERS
+        .iter()
+        .find(|c| c.ext == ext && c.name == value)
+        .map(|c| c.id)
+        .ok_or_else(|| {
+            let names: Vec<&str> = COMPILERS
+                .iter()
+                .filter(|c| c.ext == ext)
+                .map(|c| c.name)
+                .collect();
+            format!(
+                "Unknown compiler for .{} files: {}. Use: {} or a programTypeId",
+                ext,
+                value,
+                names.join(", ")
+            )
+        })
+}
+
+/// Name as shown on Codeforces, if the ID is in the table.
+pub fn describe(id: u32) -> Option<&'static str> {
+    COMPILERS.iter().find(|c| c.id == id).map(|c| c.description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_type_id() {
+        assert_eq!(program_type_id("cpp", None), Ok(89));
+        assert_eq!(program_type_id("py", None), Ok(31));
+        assert_eq!(program_type_id("py", Some("pypy3")), Ok(70));
+        assert_eq!(program_type_id("cpp", Some("clang++20")), Ok(80));
+        assert_eq!(program_type_id("cpp", Some("73")), Ok(73));
+        assert!(program_type_id("cpp", Some("pypy3")).is_err());
+        assert!(program_type_id("java", None).is_err());
+        assert_eq!(describe(75), Some("Rust 2021"));
+    }
+}
@@ -0,0 +1,698 @@
+//! Global configuration in `~/.config/cf/config.json`, and settings resolved
+//! from it, the project's `cf.toml` and environment variables.
+
+use crate::checker::Checker;
+use crate::compiler;
+use crate::layout::Layout;
+use crate::problem::DEFAULT_SITE;
+use crate::project::Project;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Environment variable selecting the active profile, unless `--profile`
+/// is given.
+pub const PROFILE_ENV: &str = "CF_PROFILE";
+
+/// A Codeforces account. The top-level `handle` and `cookies` of `Config`
+/// are the account used when no profile is active.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Profile {
+    pub handle: Option<String>,
+    pub cookies: Option<String>,
+    /// Key and secret from https://codeforces.com/settings/api
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_secret: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Config {
+    pub handle: Option<String>,
+    pub cookies: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_secret: Option<String>,
+    /// Profile used when neither `--profile` nor `CF_PROFILE` is given
+    pub profile: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Solutions layout pattern, see `layout::Layout`
+    pub layout: Option<String>,
+    pub lang: Option<String>,
+    pub editor: Option<String>,
+    pub browser: Option<String>,
+    pub time_limit_factor: Option<f64>,
+    pub color: Option<String>,
+    pub checker: Option<String>,
+    /// Network request timeout in seconds
+    pub timeout: Option<f64>,
+    pub base_url: Option<String>,
+    /// Compiler name or `programTypeId` by language extension
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub compiler: BTreeMap<String, String>,
+    /// Profile selected for this run with `--profile` or `CF_PROFILE`,
+    /// never saved
+    #[serde(skip)]
+    pub selected_profile: Option<String>,
+}
+
+impl Config {
+    /// Name of the active profile: the one selected for this run, else the
+    /// configured default.
+    pub fn active_profile(&self) -> Option<String> {
+        self.selected_profile
+            .clone()
+            .or_else(|| self.profile.clone())
+    }
+
+    /// The active account, `None` if the active profile doesn't exist.
+    pub fn account(&self) -> Option<Profile> {
+        match self.active_profile() {
+            Some(name) => self.profiles.get(&name).cloned(),
+            None => Some(Profile {
+                handle: self.handle.clone(),
+                cookies: self.cookies.clone(),
+                api_key: self.api_key.clone(),
+                api_secret: self.api_secret.clone(),
+            }),
+        }
+    }
+
+    /// The active account for writing, creating the active profile if needed.
+    pub fn account_mut(&mut self) -> AccountMut<'_> {
+        match self.active_profile() {
+            Some(name) => {
+                let profile = self.profiles.entry(name).or_default();
+                AccountMut {
+                    handle: &mut profile.handle,
+                    cookies: &mut profile.cookies,
+                    api_key: &mut profile.api_key,
+                    api_secret: &mut profile.api_secret,
+                }
+            }
+            None => AccountMut {
+                handle: &mut self.handle,
+                cookies: &mut self.cookies,
+                api_key: &mut self.api_key,
+                api_secret: &mut self.api_secret,
+            },
+        }
+    }
+}
+
+pub struct AccountMut<'a> {
+    pub handle: &'a mut Option<String>,
+    pub cookies: &'a mut Option<String>,
+    pub api_key: &'a mut Option<String>,
+    pub api_secret: &'a mut Option<String>,
+}
+
+pub fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| Path::new(".").to_path_buf())
+        .join("cf")
+        .join("config.json")
+}
+
+pub fn load_config() -> Config {
+    let path = config_path();
+    if path.exists() {
+        fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    } else {
+        Config::default()
+    }
+}
+
+/// Writes the config, readable only by the user since it holds cookies and
+/// API secrets.
+pub fn save_config(config: &Config) {
+    let path = config_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let text = serde_json::to_string_pretty(config).unwrap();
+    if let Err(e) = write_private(&path, &text) {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, text: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // `mode` only applies to new files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(text.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, text: &str) -> std::io::Result<()> {
+    fs::write(path, text)
+}
+
+/// Where a setting's value came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    Global,
+    Project,
+    Profile(String),
+    Env(&'static str),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::Global => write!(f, "global"),
+            Source::Project => write!(f, "project"),
+            Source::Profile(name) => write!(f, "profile {}", name),
+            Source::Env(var) => write!(f, "env {}", var),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Largest `time_limit_factor`, 200 s per sample.
+const MAX_TIME_LIMIT_FACTOR: f64 = 100.0;
+/// Largest `timeout` in seconds.
+const MAX_TIMEOUT: f64 = 3600.0;
+
+/// Parses a number in `(0, max]`.
+fn positive_at_most(v: &str, max: f64) -> Option<f64> {
+    v.parse::<f64>().ok().filter(|n| *n > 0.0 && *n <= max)
+}
+
+/// A setting readable with `cf config get` and writable with `cf config set`.
+pub struct Key {
+    pub name: &'static str,
+    pub env: &'static str,
+    pub description: &'static str,
+    pub default: Option<&'static str>,
+    /// Whether the key can be set in `cf.toml`
+    pub project: bool,
+    validate: fn(&str) -> Result<(), String>,
+}
+
+pub const KEYS: &[Key] = &[
+    Key {
+        name: "handle",
+        env: "CF_HANDLE",
+        description: "Codeforces handle used by watch, submit and stats",
+        default: None,
+        project: false,
+        validate: |_| Ok(()),
+    },
+    Key {
+        name: "profile",
+        env: PROFILE_ENV,
+        description: "Default profile, see cf profile",
+        default: None,
+        project: false,
+        validate: |_| Ok(()),
+    },
+    Key {
+        name: "lang",
+        env: "CF_LANG",
+        description: "Default language: py, cpp, hs, rs, kt",
+        default: Some("py"),
+        project: true,
+        validate: |v| match v {
+            "py" | "python" | "cpp" | "c++" | "hs" | "haskell" | "rs" | "rust" | "kt"
+            | "kotlin" => Ok(()),
+            _ => Err(format!("Unknown language: {}. Use: py, cpp, hs, rs, kt", v)),
+        },
+    },
+    Key {
+        name: "editor",
+        env: "CF_EDITOR",
+        description: "Command to open new solutions with (cf new --edit)",
+        default: None,
+        project: true,
+        validate: |_| Ok(()),
+    },
+    Key {
+        name: "browser",
+        env: "CF_BROWSER",
+        description: "Command to open problem and submit pages with",
+        default: None,
+        project: true,
+        validate: |_| Ok(()),
+    },
+    Key {
+        name: "time_limit_factor",
+        env: "CF_TIME_LIMIT_FACTOR",
+        description: "Multiplier for the 2 s time limit in cf test",
+        default: Some("1"),
+        project: true,
+        validate: |v| match positive_at_most(v, MAX_TIME_LIMIT_FACTOR) {
+            Some(_) => Ok(()),
+            None => Err(format!(
+                "Invalid time limit factor: {}. Use a positive number up to {}",
+                v, MAX_TIME_LIMIT_FACTOR
+            )),
+        },
+    },
+    Key {
+        name: "timeout",
+        env: "CF_TIMEOUT",
+        description: "Network request timeout in seconds",
+        default: Some("30"),
+        project: true,
+        validate: |v| match positive_at_most(v, MAX_TIMEOUT) {
+            Some(_) => Ok(()),
+            None => Err(format!(
+                "Invalid timeout: {}. Use a positive number up to {}",
+                v, MAX_TIMEOUT
+            )),
+        },
+    },
+    Key {
+        name: "base_url",
+        env: "CF_BASE_URL",
+        description: "Site to use instead of Codeforces, e.g. a mirror",
+        default: Some(DEFAULT_SITE),
+        project: true,
+        validate: |v| {
+            if v.starts_with("http://") || v.starts_with("https://") {
+                Ok(())
+            } else {
+                Err(format!("Invalid base URL: {}. Use http(s)://host", v))
+            }
+        },
+    },
+    Key {
+        name: "color",
+        env: "CF_COLOR",
+        description: "Colored output: auto, always, never",
+        default: Some("auto"),
+        project: true,
+        validate: |v| match v {
+            "auto" | "always" | "never" => Ok(()),
+            _ => Err(format!("Invalid color: {}. Use: auto, always, never", v)),
+        },
+    },
+    Key {
+        name: "layout",
+        env: "CF_LAYOUT",
+        description: "Solutions layout pattern",
+        default: Some(crate::layout::DEFAULT_LAYOUT),
+        project: true,
+        validate: |v| Layout::new(v).map(|_| ()),
+    },
+    Key {
+        name: "checker",
+        env: "CF_CHECKER",
+        description: "Output checker for cf test: trim, lines, tokens, float[:eps]",
+        default: Some("trim"),
+        project: true,
+        validate: |v| v.parse::<Checker>().map(|_| ()),
+    },
+    Key {
+        name: "compiler.cpp",
+        env: "CF_COMPILER_CPP",
+        description: "C++ compiler for cf submit, see cf submit --help",
+        default: None,
+        project: true,
+        validate: |v| compiler::parse("cpp", v).map(|_| ()),
+    },
+    Key {
+        name: "compiler.py",
+        env: "CF_COMPILER_PY",
+        description: "Python compiler for cf submit, see cf submit --help",
+        default: None,
+        project: true,
+        validate: |v| compiler::parse("py", v).map(|_| ()),
+    },
+    Key {
+        name: "compiler.rs",
+        env: "CF_COMPILER_RS",
+        description: "Rust compiler for cf submit, see cf submit --help",
+        default: None,
+        project: true,
+        validate: |v| compiler::parse("rs", v).map(|_| ()),
+    },
+    Key {
+        name: "compiler.hs",
+        env: "CF_COMPILER_HS",
+        description: "Haskell compiler for cf submit, see cf submit --help",
+        default: None,
+        project: true,
+        validate: |v| compiler::parse("hs", v).map(|_| ()),
+    },
+    Key {
+        name: "compiler.kt",
+        env: "CF_COMPILER_KT",
+        description: "Kotlin compiler for cf submit, see cf submit --help",
+        default: None,
+        project: true,
+        validate: |v| compiler::parse("kt", v).map(|_| ()),
+    },
+];
+
+pub fn find_key(name: &str) -> Result<&'static Key, String> {
+    KEYS.iter().find(|k| k.name == name).ok_or_else(|| {
+        let names: Vec<&str> = KEYS.iter().map(|k| k.name).collect();
+        format!("Unknown setting: {}. Use: {}", name, names.join(", "))
+    })
+}
+
+/// Reports an ignored environment variable, once per run.
+fn warn_once(env: &'static str, err: &str) {
+    static WARNED: Mutex<BTreeSet<&str>> = Mutex::new(BTreeSet::new());
+    if WARNED.lock().is_ok_and(|mut warned| warned.insert(env)) {
+        eprintln!("Ignoring {}: {}", env, err);
+    }
+}
+
+/// Settings resolved from, in order of precedence, environment variables,
+/// the project's `cf.toml`, the global config and built-in defaults.
+pub struct Settings<'a> {
+    project: &'a Project,
+    pub config: Config,
+    /// `--offline`: only serve responses from the cache
+    offline: bool,
+}
+
+impl<'a> Settings<'a> {
+    /// Loads the settings with `profile` (from `--profile`), else
+    /// `CF_PROFILE`, as the active profile.
+    pub fn load(project: &'a Project, profile: Option<String>) -> Settings<'a> {
+        let mut config = load_config();
+        config.selected_profile = profile
+            .or_else(|| std::env::var(PROFILE_ENV).ok())
+            .filter(|p| !p.is_empty());
+        Settings {
+            project,
+            config,
+            offline: false,
+        }
+    }
+
+    pub fn with_offline(mut self, offline: bool) -> Settings<'a> {
+        self.offline = offline;
+        self
+    }
+
+    /// Loads the settings again with the same active profile, to see what
+    /// was saved since.
+    pub fn reload(&self) -> Settings<'a> {
+        Settings::load(self.project, self.config.selected_profile.clone())
+            .with_offline(self.offline)
+    }
+
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn project(&self) -> &'a Project {
+        self.project
+    }
+
+    pub fn get(&self, name: &str) -> Option<(String, Source)> {
+        let key = find_key(name).ok()?;
+        if let Some(value) = std::env::var(key.env).ok().filter(|v| !v.is_empty()) {
+            match (key.validate)(&value) {
+                Ok(()) => return Some((value, Source::Env(key.env))),
+                Err(err) => warn_once(key.env, &err),
+            }
+        }
+        if name == "handle" {
+            if let Some(profile) = self.config.active_profile() {
+                let handle = self.config.account()?.handle?;
+                return Some((handle, Source::Profile(profile)));
+            }
+        }
+        if let Some(value) = project_value(self.project, name) {
+            return Some((value, Source::Project));
+        }
+        if let Some(value) = global_value(&self.config, name) {
+            return Some((value, Source::Global));
+        }
+        key.default.map(|d| (d.to_string(), Source::Default))
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        self.get(name).map(|(value, _)| value)
+    }
+
+    pub fn handle(&self) -> Option<String> {
+        self.value("handle")
+    }
+
+    pub fn lang(&self) -> String {
+        self.value("lang").unwrap_or_else(|| "py".to_string())
+    }
+
+    pub fn editor(&self) -> Option<String> {
+        self.value("editor")
+            .or_else(|| std::env::var("EDITOR").ok().filter(|v| !v.is_empty()))
+    }
+
+    pub fn browser(&self) -> Option<String> {
+        self.value("browser")
+    }
+
+    pub fn time_limit_factor(&self) -> f64 {
+        self.value("time_limit_factor")
+            .and_then(|v| positive_at_most(&v, MAX_TIME_LIMIT_FACTOR))
+            .unwrap_or(1.0)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.value("timeout")
+            .and_then(|v| positive_at_most(&v, MAX_TIMEOUT))
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .unwrap_or(Duration::from_secs(30))
+    }
+
+    /// Site URL without a trailing slash.
+    pub fn site(&self) -> String {
+        let site = self.value("base_url");
+        site.as_deref()
+            .unwrap_or(DEFAULT_SITE)
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    pub fn color(&self) -> Color {
+        match self.value("color").as_deref() {
+            Some("always") => Color::Always,
+            Some("never") => Color::Never,
+            _ => Color::Auto,
+        }
+    }
+
+    pub fn layout(&self) -> Option<String> {
+        self.value("layout")
+    }
+
+    pub fn checker(&self) -> Option<String> {
+        self.value("checker")
+    }
+
+    /// Configured compiler for a language extension.
+    pub fn compiler(&self, ext: &str) -> Option<String> {
+        self.value(&format!("compiler.{}", ext))
+    }
+}
+
+fn project_value(project: &Project, name: &str) -> Option<String> {
+    let config = &project.config;
+    match name {
+        "lang" => config.lang.clone(),
+        "editor" => config.editor.clone(),
+        "browser" => config.browser.clone(),
+        "time_limit_factor" => config.time_limit_factor.map(|f| f.to_string()),
+        "timeout" => config.timeout.map(|t| t.to_string()),
+        "base_url" => config.base_url.clone(),
+        "color" => config.color.clone(),
+        "layout" => config.layout.clone(),
+        "checker" => config.test.checker.clone(),
+        _ => name
+            .strip_prefix("compiler.")
+            .and_then(|ext| config.compiler.get(ext).cloned()),
+    }
+}
+
+fn global_value(config: &Config, name: &str) -> Option<String> {
+    match name {
+        "handle" => config.handle.clone(),
+        "profile" => config.profile.clone(),
+        "lang" => config.lang.clone(),
+        "editor" => config.editor.clone(),
+        "browser" => config.browser.clone(),
+        "time_limit_factor" => config.time_limit_factor.map(|f| f.to_string()),
+        "timeout" => config.timeout.map(|t| t.to_string()),
+        "base_url" => config.base_url.clone(),
+        "color" => config.color.clone(),
+        "layout" => config.layout.clone(),
+        "checker" => config.checker.clone(),
+        _ => name
+            .strip_prefix("compiler.")
+            .and_then(|ext| config.compiler.get(ext).cloned()),
+    }
+}
+
+/// Validates and stores a setting in the global config; `None` removes it.
+pub fn set_global(config: &mut Config, name: &str, value: Option<&str>) -> Result<(), String> {
+    let key = find_key(name)?;
+    if let Some(v) = value {
+        (key.validate)(v)?;
+    }
+    if let Some(v) = value.filter(|_| name == "profile") {
+        if !config.profiles.contains_key(v) {
+            return Err(format!(
+                "Unknown profile: {}. Run: cf login --profile {}",
+                v, v
+            ));
+        }
+    }
+    let value = value.map(str::to_string);
+    match name {
+        "handle" => *config.account_mut().handle = value,
+        "profile" => config.profile = value,
+        "lang" => config.lang = value,
+        "editor" => config.editor = value,
+        "browser" => config.browser = value,
+        "time_limit_factor" => config.time_limit_factor = value.and_then(|v| v.parse().ok()),
+        "timeout" => config.timeout = value.and_then(|v| v.parse().ok()),
+        "base_url" => config.base_url = value,
+        "color" => config.color = value,
+        "layout" => config.layout = value,
+        "checker" => config.checker = value,
+        _ => {
+            let ext = name.strip_prefix("compiler.").expect("all keys handled");
+            match value {
+                Some(v) => config.compiler.insert(ext.to_string(), v),
+                None => config.compiler.remove(ext),
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Validates and stores a setting in the project's `cf.toml`; `None`
+/// removes it.
+pub fn set_project(project: &Project, name: &str, value: Option<&str>) -> Result<(), String> {
+    let key = find_key(name)?;
+    if !key.project {
+        return Err(format!("{} can't be set in a project config", name));
+    }
+    if let Some(v) = value {
+        (key.validate)(v)?;
+    }
+    let value = value.map(|v| match name {
+        "time_limit_factor" | "timeout" => toml_edit::value(v.parse::<f64>().unwrap_or(1.0)),
+        _ => toml_edit::value(v),
+    });
+    match name {
+        "checker" => project.set(Some("test"), "checker", value),
+        _ => match name.strip_prefix("compiler.") {
+            Some(ext) => project.set(Some("compiler"), ext, value),
+            None => project.set(None, name, value),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_global_validates() {
+        let mut config = Config::default();
+        assert!(set_global(&mut config, "lang", Some("cpp")).is_ok());
+        assert_eq!(config.lang.as_deref(), Some("cpp"));
+        assert!(set_global(&mut config, "lang", Some("java")).is_err());
+        assert!(set_global(&mut config, "lang", Some("kt")).is_ok());
+        assert!(set_global(&mut config, "time_limit_factor", Some("-1")).is_err());
+        assert!(set_global(&mut config, "time_limit_factor", Some("1e300")).is_err());
+        assert!(set_global(&mut config, "timeout", Some("1e20")).is_err());
+        assert!(set_global(&mut config, "time_limit_factor", Some("2.5")).is_ok());
+        assert_eq!(config.time_limit_factor, Some(2.5));
+        assert!(set_global(&mut config, "color", Some("sometimes")).is_err());
+        assert!(set_global(&mut config, "layout", Some("{name}")).is_err());
+        assert!(set_global(&mut config, "nope", Some("x")).is_err());
+        assert!(set_global(&mut config, "compiler.py", Some("pypy3")).is_ok());
+        assert!(set_global(&mut config, "compiler.py", Some("clang++20")).is_err());
+        assert_eq!(
+            global_value(&config, "compiler.py").as_deref(),
+            Some("pypy3")
+        );
+        assert!(set_global(&mut config, "compiler.py", None).is_ok());
+        assert!(config.compiler.is_empty());
+        assert!(set_global(&mut config, "lang", None).is_ok());
+        assert_eq!(config.lang, None);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut config = Config {
+            handle: Some("solo".to_string()),
+            ..Config::default()
+        };
+        assert!(set_global(&mut config, "profile", Some("alice")).is_err());
+
+        config.profiles.insert(
+            "alice".to_string(),
+            Profile {
+                handle: Some("alice_cf".to_string()),
+                ..Profile::default()
+            },
+        );
+        assert_eq!(config.account().unwrap().handle.as_deref(), Some("solo"));
+        set_global(&mut config, "profile", Some("alice")).unwrap();
+        assert_eq!(
+            config.account().unwrap().handle.as_deref(),
+            Some("alice_cf")
+        );
+
+        config.profile = Some("bob".to_string());
+        assert!(config.account().is_none());
+        *config.account_mut().handle = Some("bob_cf".to_string());
+        assert_eq!(config.profiles["bob"].handle.as_deref(), Some("bob_cf"));
+        assert_eq!(config.handle.as_deref(), Some("solo"));
+
+        // `--profile` wins over the configured default
+        config.selected_profile = Some("alice".to_string());
+        assert_eq!(config.active_profile().as_deref(), Some("alice"));
+        assert_eq!(
+            config.account().unwrap().handle.as_deref(),
+            Some("alice_cf")
+        );
+    }
+
+    #[test]
+    fn test_global_value_round_trip() {
+        let mut config = Config::default();
+        for key in KEYS {
+            if let Some(default) = key.default {
+                set_global(&mut config, key.name, Some(default)).unwrap();
+                assert_eq!(global_value(&config, key.name).as_deref(), Some(default));
+            }
+        }
+    }
+}
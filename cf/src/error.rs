@@ -0,0 +1,112 @@
+//! Errors of `cf` commands, and the exit code for each kind of error.
+//!
+//! Code 2 isn't listed here: clap exits with it on invalid arguments.
+
+use crate::api::ApiError;
+use crate::session::SessionError;
+use std::fmt;
+
+/// Anything else that went wrong, like a missing file or a bad setting.
+pub const EXIT_ERROR: i32 = 1;
+/// Samples failed, also when checked before submitting, or a submission
+/// wasn't accepted.
+pub const EXIT_TESTS_FAILED: i32 = 3;
+/// A solution didn't compile.
+pub const EXIT_COMPILATION: i32 = 4;
+/// The site or the API couldn't be reached, or answered with an error.
+pub const EXIT_NETWORK: i32 = 5;
+/// No handle, session cookies or API key for what the command needs.
+pub const EXIT_NOT_LOGGED_IN: i32 = 6;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The results were printed already, so the message may be empty
+    TestsFailed(String),
+    Compilation(String),
+    Network(String),
+    NotLoggedIn(String),
+    Other(String),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::TestsFailed(_) => EXIT_TESTS_FAILED,
+            Error::Compilation(_) => EXIT_COMPILATION,
+            Error::Network(_) => EXIT_NETWORK,
+            Error::NotLoggedIn(_) => EXIT_NOT_LOGGED_IN,
+            Error::Other(_) => EXIT_ERROR,
+        }
+    }
+
+    /// The same kind of error, and exit code, with another message.
+    pub fn with_message(self, message: String) -> Error {
+        match self {
+            Error::TestsFailed(_) => Error::TestsFailed(message),
+            Error::Compilation(_) => Error::Compilation(message),
+            Error::Network(_) => Error::Network(message),
+            Error::NotLoggedIn(_) => Error::NotLoggedIn(message),
+            Error::Other(_) => Error::Other(message),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TestsFailed(message)
+            | Error::Compilation(message)
+            | Error::Network(message)
+            | Error::NotLoggedIn(message)
+            | Error::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(e: ApiError) -> Self {
+        match e {
+            ApiError::NoCredentials => Error::NotLoggedIn(e.to_string()),
+            _ => Error::Network(e.to_string()),
+        }
+    }
+}
+
+impl From<SessionError> for Error {
+    fn from(e: SessionError) -> Self {
+        match e {
+            SessionError::NotLoggedIn => Error::NotLoggedIn(e.to_string()),
+            SessionError::Rejected(_) => Error::Other(e.to_string()),
+            _ => Error::Network(e.to_string()),
+        }
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        let err: Error = ApiError::NoCredentials.into();
+        assert_eq!(err.exit_code(), EXIT_NOT_LOGGED_IN);
+        let err: Error = ApiError::Status(503).into();
+        assert_eq!(err.exit_code(), EXIT_NETWORK);
+        let err: Error = SessionError::NotLoggedIn.into();
+        assert_eq!(err.exit_code(), EXIT_NOT_LOGGED_IN);
+        let err: Error = "No solution file found for 1900A".to_string().into();
+        assert_eq!(err.exit_code(), EXIT_ERROR);
+        assert_eq!(err.to_string(), "No solution file found for 1900A");
+        let err: Error = SessionError::Network("timed out".to_string()).into();
+        assert_eq!(err.exit_code(), EXIT_NETWORK);
+        let err = err.with_message("Not submitting".to_string());
+        assert_eq!(err.exit_code(), EXIT_NETWORK);
+        assert_eq!(err.to_string(), "Not submitting");
+    }
+}
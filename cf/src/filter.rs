@@ -0,0 +1,249 @@
+//! Filters for listing submissions, as given to `cf watch`.
+
+use crate::api::Submission;
+use crate::problem::ProblemId;
+use std::str::FromStr;
+
+/// Verdict abbreviations as used on Codeforces, and the API's names.
+const VERDICTS: &[(&str, &str)] = &[
+    ("AC", "OK"),
+    ("WA", "WRONG_ANSWER"),
+    ("TLE", "TIME_LIMIT_EXCEEDED"),
+    ("MLE", "MEMORY_LIMIT_EXCEEDED"),
+    ("RE", "RUNTIME_ERROR"),
+    ("ILE", "IDLENESS_LIMIT_EXCEEDED"),
+    ("PE", "PRESENTATION_ERROR"),
+    ("CE", "COMPILATION_ERROR"),
+    ("HACKED", "CHALLENGED"),
+];
+
+/// Parses a verdict abbreviation like `WA` or an API name like
+/// `WRONG_ANSWER` into the API name.
+pub fn parse_verdict(text: &str) -> Result<String, String> {
+    let upper = text.trim().to_uppercase();
+    VERDICTS
+        .iter()
+        .find(|(short, long)| upper == *short || upper == *long)
+        .map(|(_, long)| long.to_string())
+        .or_else(|| {
+            // Other API verdicts like SKIPPED or TESTING as they are
+            (!upper.is_empty() && upper.chars().all(|c| c.is_ascii_uppercase() || c == '_'))
+                .then_some(upper)
+        })
+        .ok_or_else(|| {
+            let names: Vec<&str> = VERDICTS.iter().map(|(short, _)| *short).collect();
+            format!("Unknown verdict: {}. Use: {}", text, names.join(", "))
+        })
+}
+
+/// Whether a Codeforces language name like `GNU C++20 (64)` is of one of
+/// our languages (`cpp`, `py`, ...); anything else matches as a substring.
+pub fn language_matches(language: &str, lang: &str) -> bool {
+    let language = language.to_lowercase();
+    let names: &[&str] = match lang.to_lowercase().as_str() {
+        "cpp" | "c++" => &["c++"],
+        "py" | "python" => &["python", "pypy"],
+        "rs" | "rust" => &["rust"],
+        "hs" | "haskell" => &["haskell"],
+        "kt" | "kotlin" => &["kotlin"],
+        other => return language.contains(other),
+    };
+    names.iter().any(|n| language.contains(n))
+}
+
+/// Inclusive range like `1600..2000`, `1600..`, `..2000` or `1600`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl Range {
+    pub fn contains(&self, value: u64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl FromStr for Range {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid range: {}. Use e.g. 1600..2000, 1600.. or ..2000",
+                s
+            )
+        };
+        let bound = |b: &str| -> Result<Option<u64>, String> {
+            match b.trim() {
+                "" => Ok(None),
+                b => b.parse().map(Some).map_err(|_| invalid()),
+            }
+        };
+        let (min, max) = match s.split_once("..") {
+            Some((min, max)) => (bound(min)?, bound(max)?),
+            None => {
+                let value = bound(s)?.ok_or_else(invalid)?;
+                (Some(value), Some(value))
+            }
+        };
+        if min.is_none() && max.is_none() {
+            return Err(invalid());
+        }
+        Ok(Range { min, max })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Filter {
+    /// Problem ID, contest ID or name prefix
+    pub name: Option<String>,
+    /// API verdict names; any of them matches
+    pub verdicts: Vec<String>,
+    pub lang: Option<String>,
+    /// Problem tags; all of them must be present
+    pub tags: Vec<String>,
+    /// Problem rating; unrated problems never match
+    pub rating: Option<Range>,
+    pub contest: Option<Range>,
+}
+
+impl Filter {
+    pub fn matches(&self, sub: &Submission) -> bool {
+        let contest_id = sub.contest_id.unwrap_or(0);
+        if let Some(name) = &self.name {
+            let matches = if let Ok(contest) = name.parse::<u64>() {
+                contest_id == contest
+            } else if let Ok(id) = name.parse::<ProblemId>() {
+                sub.problem.short_name() == id.to_string()
+            } else {
+                let short = sub.problem.short_name().to_lowercase();
+                short.starts_with(&name.to_lowercase())
+            };
+            if !matches {
+                return false;
+            }
+        }
+        if !self.verdicts.is_empty() {
+            let verdict = sub.verdict.as_deref().unwrap_or("TESTING");
+            if !self.verdicts.iter().any(|v| v == verdict) {
+                return false;
+            }
+        }
+        if let Some(lang) = &self.lang {
+            if !language_matches(&sub.programming_language, lang) {
+                return false;
+            }
+        }
+        let has_tag = |tag: &String| sub.problem.tags.iter().any(|t| t.eq_ignore_ascii_case(tag));
+        if !self.tags.iter().all(has_tag) {
+            return false;
+        }
+        if let Some(rating) = self.rating {
+            if !sub
+                .problem
+                .rating
+                .is_some_and(|r| rating.contains(r as u64))
+            {
+                return false;
+            }
+        }
+        if let Some(contest) = self.contest {
+            if !contest.contains(contest_id) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submission(
+        contest: u64,
+        index: &str,
+        rating: u32,
+        language: &str,
+        verdict: &str,
+    ) -> Submission {
+        serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "contestId": contest,
+            "creationTimeSeconds": 0,
+            "problem": {
+                "contestId": contest,
+                "index": index,
+                "name": "X",
+                "rating": rating,
+                "tags": ["dp", "greedy"]
+            },
+            "programmingLanguage": language,
+            "verdict": verdict,
+            "passedTestCount": 0,
+            "timeConsumedMillis": 0,
+            "memoryConsumedBytes": 0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_verdict("wa").unwrap(), "WRONG_ANSWER");
+        assert_eq!(
+            parse_verdict("TIME_LIMIT_EXCEEDED").unwrap(),
+            "TIME_LIMIT_EXCEEDED"
+        );
+        assert_eq!(parse_verdict("skipped").unwrap(), "SKIPPED");
+        assert!(parse_verdict("w-a").is_err());
+
+        let range: Range = "1600..2000".parse().unwrap();
+        assert!(range.contains(1600) && range.contains(2000) && !range.contains(2100));
+        let range: Range = "..1000".parse().unwrap();
+        assert!(range.contains(0) && !range.contains(1001));
+        assert_eq!("1900".parse::<Range>().unwrap().max, Some(1900));
+        assert!("..".parse::<Range>().is_err());
+        assert!("a..b".parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        let sub = submission(1900, "A1", 1700, "GNU C++20 (64)", "WRONG_ANSWER");
+        let filter = |f: Filter| f.matches(&sub);
+
+        assert!(filter(Filter::default()));
+        assert!(!filter(Filter {
+            name: Some("1900A".to_string()),
+            ..Filter::default()
+        }));
+        assert!(filter(Filter {
+            name: Some("1900A1".to_string()),
+            ..Filter::default()
+        }));
+        assert!(!filter(Filter {
+            name: Some("190".to_string()),
+            ..Filter::default()
+        }));
+        assert!(filter(Filter {
+            verdicts: vec!["OK".to_string(), "WRONG_ANSWER".to_string()],
+            lang: Some("cpp".to_string()),
+            tags: vec!["DP".to_string()],
+            rating: Some("1600..2000".parse().unwrap()),
+            contest: Some("1900..".parse().unwrap()),
+            ..Filter::default()
+        }));
+        assert!(!filter(Filter {
+            lang: Some("py".to_string()),
+            ..Filter::default()
+        }));
+        assert!(!filter(Filter {
+            tags: vec!["dp".to_string(), "math".to_string()],
+            ..Filter::default()
+        }));
+        assert!(!filter(Filter {
+            contest: Some("..1899".parse().unwrap()),
+            ..Filter::default()
+        }));
+    }
+}
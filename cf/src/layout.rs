@@ -0,0 +1,338 @@
+//! Where solution files live inside `solutions/`, described by a pattern
+//! such as `{set}/{name}.{ext}` or `contests/{contest}/{letter}.{ext}`.
+
+use crate::problem::{ProblemId, ProblemKind};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// The original `A-set/1900A.py` layout.
+pub const DEFAULT_LAYOUT: &str = "{set}/{name}.{ext}";
+
+/// Non-Codeforces problems go here when the layout needs contest details.
+const OTHERS_LAYOUT: &str = "Others/{name}.{ext}";
+
+const PLACEHOLDERS: &[&str] = &["name", "contest", "letter", "set", "kind", "rating", "ext"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Placeholder(String),
+}
+
+/// A parsed layout pattern. Placeholders:
+///
+/// - `{name}`: problem name (`1900A`, `sgu100`, `leetcode`)
+/// - `{contest}`, `{letter}`: contest ID and problem index (`1900`, `F1`)
+/// - `{set}`: `A-set` style group, or `Gym`, `Acmsguru`, `Others`
+/// - `{kind}`: `contest`, `gym` or `acmsguru`
+/// - `{rating}`: problem rating from the API, `unrated` if unknown
+/// - `{ext}`: file extension
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pattern: String,
+    tokens: Vec<Token>,
+}
+
+impl Layout {
+    pub fn new(pattern: &str) -> Result<Layout, String> {
+        // Solutions must stay inside `solutions/`
+        let escapes = Path::new(pattern).components().any(|c| {
+            matches!(
+                c,
+                Component::RootDir | Component::Prefix(_) | Component::ParentDir
+            )
+        });
+        if escapes {
+            return Err(format!(
+                "Layout {} must be a relative path without ..",
+                pattern
+            ));
+        }
+        let tokens = tokenize(pattern)?;
+        let has = |name: &str| tokens.contains(&Token::Placeholder(name.to_string()));
+        if !has("ext") {
+            return Err(format!("Layout {} must contain {{ext}}", pattern));
+        }
+        let identifies_problem = has("name") || (has("contest") && has("letter"));
+        if !identifies_problem {
+            return Err(format!(
+                "Layout {} must contain {{name}} or both {{contest}} and {{letter}}",
+                pattern
+            ));
+        }
+        Ok(Layout {
+            pattern: pattern.to_string(),
+            tokens,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn needs_rating(&self) -> bool {
+        self.uses("rating")
+    }
+
+    fn uses(&self, placeholder: &str) -> bool {
+        self.tokens
+            .contains(&Token::Placeholder(placeholder.to_string()))
+    }
+
+    /// Layout used for `name`: problems that aren't Codeforces problems fall
+    /// back to `Others/` when the pattern needs contest details.
+    fn for_name(&self, name: &str) -> Layout {
+        let needs_problem = ["contest", "letter", "kind", "rating"]
+            .iter()
+            .any(|p| self.uses(p));
+        if needs_problem && name.parse::<ProblemId>().is_err() {
+            Layout::new(OTHERS_LAYOUT).expect("valid fallback layout")
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Path of the solution relative to `solutions/`.
+    pub fn render(&self, name: &str, ext: &str, rating: Option<u32>) -> PathBuf {
+        let layout = self.for_name(name);
+        let id = name.parse::<ProblemId>().ok();
+        let mut out = String::new();
+        for token in &layout.tokens {
+            match token {
+                Token::Literal(s) => out.push_str(s),
+                Token::Placeholder(p) => out.push_str(&match p.as_str() {
+                    "name" => name.to_string(),
+                    "ext" => ext.to_string(),
+                    "set" => set_name(id.as_ref()),
+                    "rating" => rating.map_or("unrated".to_string(), |r| r.to_string()),
+                    // Only reachable with a valid problem ID, see `for_name`
+                    "contest" => id
+                        .as_ref()
+                        .map_or(String::new(), |id| id.contest.to_string()),
+                    "letter" => id.as_ref().map_or(String::new(), |id| id.index.clone()),
+                    "kind" => id.as_ref().map_or(String::new(), |id| kind_name(id.kind)),
+                    _ => String::new(),
+                }),
+            }
+        }
+        PathBuf::from(out)
+    }
+
+    /// Recovers `(name, ext)` from a path relative to `solutions/`, or `None`
+    /// if the path doesn't follow this layout.
+    pub fn parse_path(&self, path: &Path) -> Option<(String, String)> {
+        let path = path.to_str()?.replace('\\', "/");
+        let others = Layout::new(OTHERS_LAYOUT).expect("valid fallback layout");
+        [self, &others].iter().find_map(|layout| {
+            let captures = layout.captures(&path)?;
+            let ext = captures.get("ext")?.clone();
+            let name = match captures.get("name") {
+                Some(name) => name.clone(),
+                None => ProblemId::from_parts(captures.get("contest")?, captures.get("letter")?)
+                    .ok()?
+                    .to_string(),
+            };
+            let rating = captures.get("rating").and_then(|r| r.parse().ok());
+
+            // Only accept the path we'd generate for this problem
+            (self.render(&name, &ext, rating) == Path::new(&path)).then_some((name, ext))
+        })
+    }
+
+    fn captures(&self, path: &str) -> Option<HashMap<String, String>> {
+        let mut captures = HashMap::new();
+        match_tokens(&self.tokens, path, &mut captures).then_some(captures)
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::new(DEFAULT_LAYOUT).expect("valid default layout")
+    }
+}
+
+fn set_name(id: Option<&ProblemId>) -> String {
+    match id {
+        Some(id) => match id.kind {
+            ProblemKind::Contest => format!("{}-set", id.letter()),
+            ProblemKind::Gym => "Gym".to_string(),
+            ProblemKind::Acmsguru => "Acmsguru".to_string(),
+        },
+        None => "Others".to_string(),
+    }
+}
+
+fn kind_name(kind: ProblemKind) -> String {
+    match kind {
+        ProblemKind::Contest => "contest",
+        ProblemKind::Gym => "gym",
+        ProblemKind::Acmsguru => "acmsguru",
+    }
+    .to_string()
+}
+
+fn tokenize(pattern: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = pattern;
+    while !rest.is_empty() {
+        match rest.find('{') {
+            Some(0) => {
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| format!("Unclosed {{ in layout {}", pattern))?;
+                let name = &rest[1..end];
+                if !PLACEHOLDERS.contains(&name) {
+                    return Err(format!(
+                        "Unknown placeholder {{{}}} in layout {}. Use: {}",
+                        name,
+                        pattern,
+                        PLACEHOLDERS
+                            .iter()
+                            .map(|p| format!("{{{}}}", p))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+                if matches!(tokens.last(), Some(Token::Placeholder(_))) {
+                    return Err(format!(
+                        "Placeholders in layout {} must be separated by text",
+                        pattern
+                    ));
+                }
+                tokens.push(Token::Placeholder(name.to_string()));
+                rest = &rest[end + 1..];
+            }
+            Some(i) => {
+                tokens.push(Token::Literal(rest[..i].to_string()));
+                rest = &rest[i..];
+            }
+            None => {
+                tokens.push(Token::Literal(rest.to_string()));
+                rest = "";
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Matches `s` against the tokens, backtracking over where each placeholder
+/// ends. Placeholders never span a `/` and must agree when repeated.
+fn match_tokens(tokens: &[Token], s: &str, captures: &mut HashMap<String, String>) -> bool {
+    let Some((first, rest)) = tokens.split_first() else {
+        return s.is_empty();
+    };
+    match first {
+        Token::Literal(lit) => s
+            .strip_prefix(lit.as_str())
+            .is_some_and(|s| match_tokens(rest, s, captures)),
+        Token::Placeholder(name) => {
+            if let Some(value) = captures.get(name).cloned() {
+                return s
+                    .strip_prefix(value.as_str())
+                    .is_some_and(|s| match_tokens(rest, s, captures));
+            }
+            let segment_end = s.find('/').unwrap_or(s.len());
+            for end in (1..=segment_end).rev() {
+                if !s.is_char_boundary(end) {
+                    continue;
+                }
+                captures.insert(name.clone(), s[..end].to_string());
+                if match_tokens(rest, &s[end..], captures) {
+                    return true;
+                }
+            }
+            captures.remove(name);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_default() {
+        let layout = Layout::default();
+        assert_eq!(
+            layout.render("1900A", "py", None),
+            Path::new("A-set/1900A.py")
+        );
+        assert_eq!(
+            layout.render("102001K", "cpp", None),
+            Path::new("Gym/102001K.cpp")
+        );
+        assert_eq!(
+            layout.render("leetcode", "py", None),
+            Path::new("Others/leetcode.py")
+        );
+    }
+
+    #[test]
+    fn test_render_custom() {
+        let layout = Layout::new("contests/{contest}/{letter}.{ext}").unwrap();
+        assert_eq!(
+            layout.render("2000F1", "cpp", None),
+            Path::new("contests/2000/F1.cpp")
+        );
+        assert_eq!(
+            layout.render("leetcode", "py", None),
+            Path::new("Others/leetcode.py")
+        );
+
+        let layout = Layout::new("{rating}/{name}.{ext}").unwrap();
+        assert!(layout.needs_rating());
+        assert_eq!(
+            layout.render("1900A", "py", Some(800)),
+            Path::new("800/1900A.py")
+        );
+        assert_eq!(
+            layout.render("1900A", "py", None),
+            Path::new("unrated/1900A.py")
+        );
+    }
+
+    #[test]
+    fn test_parse_path() {
+        let layout = Layout::default();
+        let parse = |l: &Layout, p: &str| l.parse_path(Path::new(p));
+        assert_eq!(
+            parse(&layout, "A-set/1900A.py"),
+            Some(("1900A".to_string(), "py".to_string()))
+        );
+        assert_eq!(parse(&layout, "B-set/1900A.py"), None);
+        assert_eq!(
+            parse(&layout, "Others/leetcode.py"),
+            Some(("leetcode".to_string(), "py".to_string()))
+        );
+
+        let layout = Layout::new("contests/{contest}/{letter}.{ext}").unwrap();
+        assert_eq!(
+            parse(&layout, "contests/2000/F1.cpp"),
+            Some(("2000F1".to_string(), "cpp".to_string()))
+        );
+        assert_eq!(parse(&layout, "A-set/1900A.py"), None);
+
+        let layout = Layout::new("{rating}/{name}.{ext}").unwrap();
+        assert_eq!(
+            parse(&layout, "1600/1900A.py"),
+            Some(("1900A".to_string(), "py".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_layouts() {
+        assert!(Layout::new("{name}").is_err());
+        assert!(Layout::new("{contest}/{name}.{foo}").is_err());
+        assert!(Layout::new("{contest}.{ext}").is_err());
+        assert!(Layout::new("{contest}{letter}.{ext}").is_err());
+        assert!(Layout::new("{name.{ext}").is_err());
+    }
+
+    #[test]
+    fn test_layouts_outside_solutions() {
+        assert!(Layout::new("/tmp/{name}.{ext}").is_err());
+        assert!(Layout::new("../{name}.{ext}").is_err());
+        assert!(Layout::new("{set}/../../{name}.{ext}").is_err());
+    }
+}
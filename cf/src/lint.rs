@@ -0,0 +1,100 @@
+//! Checks of a solution's source before it's submitted.
+
+/// Lines that look like leftover debug output, as (line number, line).
+/// Code inside `#ifdef LOCAL` blocks is skipped, since it isn't compiled on
+/// Codeforces unless the source itself defines `LOCAL`.
+pub fn debug_lines(source: &str, ext: &str) -> Vec<(usize, String)> {
+    let patterns: &[&str] = match ext {
+        "cpp" => &["cerr", "#define LOCAL", "fprintf(stderr"],
+        "py" => &["file=sys.stderr", "sys.stderr.write", "breakpoint()"],
+        "rs" => &["eprintln!", "eprint!", "dbg!"],
+        "hs" => &["Debug.Trace", "traceShow", "hPutStrLn stderr"],
+        "kt" => &["System.err"],
+        _ => &[],
+    };
+
+    let mut found = Vec::new();
+    // Nesting depth of preprocessor conditionals, and the depth at which an
+    // `#ifdef LOCAL` block started
+    let mut depth = 0;
+    let mut local_depth = None;
+    for (i, line) in source.lines().enumerate() {
+        let code = line.trim();
+        if ext == "cpp" {
+            if code.starts_with("#if") {
+                depth += 1;
+                if local_depth.is_none() && code.split_whitespace().eq(["#ifdef", "LOCAL"]) {
+                    local_depth = Some(depth);
+                }
+                continue;
+            }
+            if code.starts_with("#el") {
+                // The other branch is what Codeforces compiles
+                if local_depth == Some(depth) {
+                    local_depth = None;
+                }
+                continue;
+            }
+            if code.starts_with("#endif") {
+                if local_depth == Some(depth) {
+                    local_depth = None;
+                }
+                depth -= 1;
+                continue;
+            }
+            if local_depth.is_some() {
+                continue;
+            }
+        }
+        let comment = match ext {
+            "py" => "#",
+            "hs" => "--",
+            _ => "//",
+        };
+        if code.starts_with(comment) {
+            continue;
+        }
+        if patterns.iter().any(|p| code.contains(p)) {
+            found.push((i + 1, code.to_string()));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_lines() {
+        let cpp = "#include <bits/stdc++.h>\n\
+                   #ifdef LOCAL\n\
+                   #define dbg(x) cerr << #x << x\n\
+                   #else\n\
+                   #define dbg(x) fprintf(stderr, \"%d\", x)\n\
+                   #endif\n\
+                   int main() {\n\
+                   \x20   // cerr << \"commented out\";\n\
+                   \x20   cerr << \"here\";\n\
+                   }\n";
+        assert_eq!(
+            debug_lines(cpp, "cpp"),
+            [
+                (5, "#define dbg(x) fprintf(stderr, \"%d\", x)".to_string()),
+                (9, "cerr << \"here\";".to_string())
+            ]
+        );
+        assert_eq!(
+            debug_lines("#define LOCAL\nint main() {}\n", "cpp").len(),
+            1
+        );
+
+        let py = "import sys\n# print(x, file=sys.stderr)\nprint(x, file=sys.stderr)\n";
+        assert_eq!(
+            debug_lines(py, "py"),
+            [(3, "print(x, file=sys.stderr)".to_string())]
+        );
+        assert!(debug_lines("print(1)\n", "py").is_empty());
+        assert_eq!(debug_lines("fn main() { dbg!(1); }", "rs").len(), 1);
+    }
+}
@@ -1,15 +1,40 @@
-use clap::{Parser, Subcommand};
-use serde::{Deserialize, Serialize};
+mod age;
+mod api;
+mod bundle;
+mod cache;
+mod checker;
+mod compiler;
+mod config;
+mod error;
+mod filter;
+mod layout;
+mod lint;
+mod migrate;
+mod problem;
+mod project;
+mod report;
+mod samples;
+mod session;
+mod snippets;
+
+use checker::Checker;
+use clap::{Args, Parser, Subcommand};
+use config::{save_config, Color, Settings, Source};
+use error::Error;
+use layout::Layout;
+use problem::{ProblemId, ProblemKind, GYM_CONTEST_MIN};
+use project::Project;
+use report::{TestCase, TestRun};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Serialize, Deserialize, Default)]
-struct Config {
-    handle: Option<String>,
-    cookies: Option<String>,
-}
+/// Codeforces' most common time limit, scaled by `time_limit_factor`.
+const BASE_TIME_LIMIT: Duration = Duration::from_secs(2);
 
 #[derive(Parser)]
 #[command(name = "cf")]
@@ -18,11 +43,22 @@ struct Config {
     long_about = "Generate solution files from templates for competitive programming.\n\n\
     Files are organized into:\n  \
     - solutions/{A,B,C,...}-set/ for Codeforces problems (e.g., 1900A)\n  \
+    - solutions/Gym/ for gym problems (e.g., 102001K)\n  \
+    - solutions/Acmsguru/ for acm.sgu problems (e.g., sgu100)\n  \
     - solutions/Others/ for non-CF problems"
 )]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Account profile to use (default: CF_PROFILE, else the configured one)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    /// Only use cached responses, never the network
+    #[arg(long, global = true)]
+    offline: bool,
+    /// Print JSON instead of text (test, watch, list and stats)
+    #[arg(long, global = true)]
+    json: bool,
 }
 
 #[derive(Subcommand)]
@@ -31,54 +67,247 @@ enum Commands {
     New {
         /// Problem name (e.g., 1900A -> A-set/, leetcode -> Others/)
         name: String,
-        /// Language: py, cpp, hs (default: py)
-        #[arg(short, long, default_value = "py")]
-        lang: String,
+        /// Language: py, cpp, hs, rs, kt (default: from cf.toml, else py)
+        #[arg(short, long)]
+        lang: Option<String>,
         /// Use single test case template (no t loop)
         #[arg(short, long)]
         single: bool,
         /// Use fast I/O template (for large inputs)
         #[arg(short, long)]
         fast: bool,
+        /// Open the new file in the configured editor
+        #[arg(short, long)]
+        edit: bool,
     },
     /// List available templates and solutions
     List,
     /// Create sample I/O files for a problem
     Eg {
-        /// Problem name (e.g., 1900A)
+        /// Problem ID or URL (e.g., 1900A, 2000F1, gym/102001/problem/K)
         name: String,
-        /// Number of sample sets to create (default: 1)
+        /// Number of empty sample sets to create when the samples can't be
+        /// downloaded (default: 1)
         #[arg(default_value = "1")]
         count: usize,
     },
     /// Test solution against sample I/O
     Test {
-        /// Problem name (e.g., 1900A)
-        name: String,
+        /// Problem ID or URL (e.g., 1900A, 2000F1)
+        #[arg(required_unless_present = "all")]
+        name: Option<String>,
         /// Specific test number (omit to run all)
         num: Option<usize>,
-        /// Language: py, cpp, hs (default: py)
-        #[arg(short, long, default_value = "py")]
-        lang: String,
+        /// Output checker: trim, lines, tokens, float[:eps] (default: from cf.toml, else trim)
+        #[arg(short, long)]
+        checker: Option<String>,
+        /// Language: py, cpp, hs, rs, kt (default: from cf.toml, else py)
+        #[arg(short, long)]
+        lang: Option<String>,
+        /// Test every solution that has samples, in all languages (or only --lang)
+        #[arg(long, conflicts_with_all = ["name", "num"])]
+        all: bool,
+        /// Write a JUnit XML report to this file
+        #[arg(long)]
+        report: Option<PathBuf>,
     },
-    /// Login to Codeforces
-    Login,
-    /// Watch your submissions on Codeforces
-    Watch {
-        /// Problem name (e.g., 1900A) or contest ID (e.g., 1900)
-        name: Option<String>,
-        /// Only accepted submissions
+    /// Login to Codeforces (into the active profile, if any)
+    Login {
+        /// Save an API key and secret instead, for private contests and friends
+        #[arg(long)]
+        api_key: bool,
+    },
+    /// List your friends (needs an API key)
+    Friends {
+        /// Only friends online now
         #[arg(short, long)]
-        ac: bool,
+        online: bool,
+    },
+    /// Watch your submissions on Codeforces
+    Watch(WatchArgs),
+    /// Show rating and solved problems of a handle
+    Stats {
+        /// Handle (default: the active profile's)
+        handle: Option<String>,
+    },
+    /// Show the standings of a contest (private ones need an API key)
+    Standings {
+        /// Contest or gym ID
+        contest: u64,
+        /// Number of rows to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Manage account profiles
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
     },
     /// Submit solution to Codeforces
     Submit {
-        /// Problem name (e.g., 1900A)
+        /// Problem ID or URL (e.g., 1900A, 2000F1)
+        name: String,
+        /// Open the submit page in the browser instead of submitting
+        #[arg(short, long)]
+        browser: bool,
+        /// Language (py, cpp, rs, hs, kt) or compiler (e.g. pypy3, clang++20)
+        #[arg(short, long)]
+        lang: Option<String>,
+        /// Submit even if samples fail or the file is of another language
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Move existing solutions (and samples) to a new layout
+    Migrate {
+        /// New layout pattern (e.g., "contests/{contest}/{letter}.{ext}")
+        layout: String,
+        /// Current layout pattern (default: the configured one)
+        #[arg(long)]
+        from: Option<String>,
+        /// Only list the moves
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+        /// Move the other files even if some don't match the current layout
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Manage code snippets from the snippets/ directory
+    Lib {
+        #[command(subcommand)]
+        command: LibCommands,
+    },
+    /// Inspect or clear the cache of downloaded pages and API responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+    /// Read and write settings
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Args)]
+struct WatchArgs {
+    /// Problem name (e.g., 1900A) or contest ID (e.g., 1900)
+    name: Option<String>,
+    /// Only accepted submissions
+    #[arg(short, long)]
+    ac: bool,
+    /// Only gym submissions
+    #[arg(short, long)]
+    gym: bool,
+    /// Only these verdicts (e.g., WA,TLE)
+    #[arg(long, value_delimiter = ',', value_parser = filter::parse_verdict)]
+    verdict: Vec<String>,
+    /// Only this language (py, cpp, rs, hs, kt or part of the compiler name)
+    #[arg(short, long)]
+    lang: Option<String>,
+    /// Only problems with all these tags (e.g., dp,greedy)
+    #[arg(long, value_delimiter = ',')]
+    tag: Vec<String>,
+    /// Only problems rated in this range (e.g., 1600..2000, 1600.., ..1200)
+    #[arg(long)]
+    rating: Option<filter::Range>,
+    /// Only contests in this range (e.g., 1900..1950)
+    #[arg(long)]
+    contest: Option<filter::Range>,
+    /// Number of submissions to show, 0 for all
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+    /// Only submissions newer than this (e.g., 30m, 12h, 2d, 1w)
+    #[arg(short, long, value_parser = age::parse)]
+    since: Option<i64>,
+    /// Search at most this many pages of 100 submissions for matches, 0 for
+    /// the whole history (each page is an API call, about 2 s)
+    #[arg(long, default_value_t = 5)]
+    pages: usize,
+    /// Keep refreshing until interrupted, highlighting changes
+    #[arg(short, long)]
+    follow: bool,
+    /// Seconds between refreshes with --follow (at least 2)
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// List profiles, marking the active one
+    List,
+    /// Make a profile the default
+    Use {
+        /// Profile name (create it with cf login --profile <name>)
+        name: String,
+    },
+    /// Delete a profile and its saved credentials
+    Remove {
+        /// Profile name
         name: String,
     },
 }
 
-fn get_template_dir() -> std::path::PathBuf {
+#[derive(Subcommand)]
+enum CacheCommands {
+    /// Show cached responses by kind
+    Stats,
+    /// Delete cached responses
+    Clear {
+        /// Only delete responses that are no longer fresh
+        #[arg(short, long)]
+        expired: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Show every setting with its value and where it came from
+    List,
+    /// Print the value of a setting
+    Get {
+        /// Setting name (e.g., lang)
+        key: String,
+    },
+    /// Change a setting in the global config (or cf.toml with --project)
+    Set {
+        /// Setting name (e.g., lang)
+        key: String,
+        value: String,
+        /// Write to the project's cf.toml
+        #[arg(short, long)]
+        project: bool,
+    },
+    /// Remove a setting from the global config (or cf.toml with --project)
+    Unset {
+        /// Setting name (e.g., lang)
+        key: String,
+        /// Remove from the project's cf.toml
+        #[arg(short, long)]
+        project: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum LibCommands {
+    /// List available snippets and their languages
+    List,
+    /// Insert a snippet into a solution at its `cf:lib` marker comment
+    Add {
+        /// Problem ID or URL (e.g., 1900A, 2000F1)
+        name: String,
+        /// Snippet name (e.g., segtree)
+        snippet: String,
+        /// Language: py, cpp, hs, rs, kt (default: from cf.toml, else py)
+        #[arg(short, long)]
+        lang: Option<String>,
+    },
+}
+
+fn get_template_dir(project: &Project) -> std::path::PathBuf {
+    if let Some(dir) = project.templates_dir() {
+        return dir;
+    }
+
     let from_exe = std::env::current_exe().ok().and_then(|mut path| {
         for _ in 0..3 {
             path = path.parent()?.to_path_buf();
@@ -92,96 +321,167 @@ fn get_template_dir() -> std::path::PathBuf {
         }
     }
 
-    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    let candidates = [cwd.join("cf").join("templates"), cwd.join("templates")];
+    let root = project.root();
+    let candidates = [root.join("cf").join("templates"), root.join("templates")];
     candidates
         .into_iter()
         .find(|p| p.is_dir())
         .unwrap_or_else(|| PathBuf::from("templates"))
 }
 
-fn get_config_path() -> std::path::PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| Path::new(".").to_path_buf())
-        .join("cf")
-        .join("config.json")
+/// Canonical name for files and samples: problem IDs and URLs become `1900A`,
+/// anything else (e.g. `leetcode`) is kept as is.
+fn canonical_name(name: &str) -> String {
+    name.parse::<ProblemId>()
+        .map(|id| id.to_string())
+        .unwrap_or_else(|_| name.to_string())
 }
 
-fn load_config() -> Config {
-    let path = get_config_path();
-    if path.exists() {
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|s| serde_json::from_str(&s).ok())
-            .unwrap_or_default()
-    } else {
-        Config::default()
-    }
+/// Language from the command line, else from the settings.
+fn resolve_lang(settings: &Settings, lang: Option<String>) -> String {
+    lang.unwrap_or_else(|| settings.lang())
 }
 
-fn save_config(config: &Config) {
-    let path = get_config_path();
-    if let Some(parent) = path.parent() {
-        let _ = fs::create_dir_all(parent);
+/// The checker given on the command line, else the configured one.
+fn resolve_checker(settings: &Settings, checker: Option<String>) -> Result<Checker, String> {
+    match checker.or(settings.checker()) {
+        Some(c) => c.parse::<Checker>(),
+        None => Ok(Checker::default()),
     }
-    let _ = fs::write(&path, serde_json::to_string_pretty(config).unwrap());
 }
 
-fn parse_problem_name(name: &str) -> Option<(String, String)> {
-    // Parse "1900A" -> ("1900", "A")
-    let chars: Vec<char> = name.chars().collect();
-    if chars.len() < 2 {
-        return None;
+fn load_layout(settings: &Settings) -> Layout {
+    match settings.layout() {
+        Some(pattern) => Layout::new(&pattern).unwrap_or_else(|err| {
+            eprintln!("{}. Using {}", err, layout::DEFAULT_LAYOUT);
+            Layout::default()
+        }),
+        None => Layout::default(),
     }
+}
 
-    // Find where digits end and letter begins
-    let mut split_idx = chars.len();
-    for (i, c) in chars.iter().enumerate().rev() {
-        if c.is_ascii_digit() {
-            split_idx = i + 1;
-            break;
-        }
-    }
+/// Every file under `solutions/` that follows the layout, as
+/// `(path, name, ext)`.
+fn list_solution_files(project: &Project, layout: &Layout) -> Vec<(PathBuf, String, String)> {
+    let root = project.solutions_dir();
+    list_files(&root)
+        .into_iter()
+        .filter_map(|path| {
+            let (name, ext) = parse_solution_path(&root, layout, &path)?;
+            Some((path, name, ext))
+        })
+        .collect()
+}
 
-    if split_idx == 0 || split_idx == chars.len() {
-        return None;
+/// Every file below `root`, sorted.
+fn list_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
     }
+    files.sort();
+    files
+}
 
-    let contest_id: String = chars[..split_idx].iter().collect();
-    let problem_letter: String = chars[split_idx..].iter().collect();
+/// Problem name and extension of a solution, if `layout` matches its path.
+fn parse_solution_path(root: &Path, layout: &Layout, path: &Path) -> Option<(String, String)> {
+    layout.parse_path(path.strip_prefix(root).ok()?)
+}
 
-    Some((contest_id, problem_letter.to_uppercase()))
+/// API client signing requests with the active account's key, if any.
+fn api_client(settings: &Settings) -> api::Client {
+    let credentials = settings.config.account().and_then(|account| {
+        Some(api::Credentials {
+            key: account.api_key?,
+            secret: account.api_secret?,
+        })
+    });
+    api::Client::new(&settings.site(), settings.timeout())
+        .with_credentials(credentials)
+        .with_offline(settings.offline())
 }
 
-fn is_cf_problem(name: &str) -> bool {
-    // CF problems are like "1900A", "123B" - digits followed by a letter
-    let chars: Vec<char> = name.chars().collect();
-    if chars.len() < 2 {
-        return false;
+/// Rating of a contest problem from the API, if it has one.
+fn fetch_problem_rating(settings: &Settings, name: &str) -> Option<u32> {
+    let id = name.parse::<ProblemId>().ok()?;
+    if id.kind != ProblemKind::Contest {
+        return None;
     }
-    let last = chars[chars.len() - 1];
-    let has_digits = chars[..chars.len() - 1].iter().any(|c| c.is_ascii_digit());
-    last.is_ascii_alphabetic() && has_digits
+
+    // One cached list serves every problem, e.g. during a migration
+    let problems = api_client(settings).problemset_problems().ok()?;
+    problems
+        .iter()
+        .find(|p| p.contest_id == Some(id.contest) && p.index == id.index)?
+        .rating
 }
 
-fn extract_problem_letter(name: &str) -> Option<char> {
-    if is_cf_problem(name) {
-        name.chars().last()
-    } else {
-        None
+fn lang_ext(lang: &str) -> Option<&'static str> {
+    match lang {
+        "py" | "python" => Some("py"),
+        "cpp" | "c++" => Some("cpp"),
+        "hs" | "haskell" => Some("hs"),
+        "rs" | "rust" => Some("rs"),
+        "kt" | "kotlin" => Some("kt"),
+        _ => None,
     }
 }
 
-fn generate(name: &str, lang: &str, single: bool, fast: bool) {
-    let ext = match lang {
-        "py" | "python" => "py",
-        "cpp" | "c++" => "cpp",
-        "hs" | "haskell" => "hs",
-        _ => {
-            eprintln!("Unknown language: {}. Use: py, cpp, hs", lang);
+/// Opens a URL with the configured browser command, or the system default.
+fn open_browser(settings: &Settings, url: &str) {
+    if let Some(browser) = settings.browser() {
+        let mut parts = browser.split_whitespace();
+        if let Some(program) = parts.next() {
+            if let Err(e) = Command::new(program).args(parts).arg(url).spawn() {
+                eprintln!("Failed to run {}: {}", browser, e);
+            }
             return;
         }
+    }
+
+    #[cfg(target_os = "macos")]
+    let _ = Command::new("open").arg(url).spawn();
+    #[cfg(target_os = "linux")]
+    let _ = Command::new("xdg-open").arg(url).spawn();
+    #[cfg(target_os = "windows")]
+    let _ = Command::new("cmd").args(["/C", "start", url]).spawn();
+}
+
+fn open_editor(settings: &Settings, path: &Path) {
+    let Some(editor) = settings.editor() else {
+        eprintln!("No editor configured. Run: cf config set editor <command>");
+        return;
     };
+    let mut parts = editor.split_whitespace();
+    if let Some(program) = parts.next() {
+        if let Err(e) = Command::new(program).args(parts).arg(path).status() {
+            eprintln!("Failed to run {}: {}", editor, e);
+        }
+    }
+}
+
+fn generate(
+    settings: &Settings,
+    name: &str,
+    lang: &str,
+    single: bool,
+    fast: bool,
+    edit: bool,
+) -> Result<(), Error> {
+    let project = settings.project();
+    let ext = lang_ext(lang)
+        .ok_or_else(|| format!("Unknown language: {}. Use: py, cpp, hs, rs, kt", lang))?;
 
     // Select template variant (only for Python)
     let template_file: String = if ext == "py" {
@@ -196,67 +496,83 @@ fn generate(name: &str, lang: &str, single: bool, fast: bool) {
         format!("template.{}", ext)
     };
 
-    let template_path = get_template_dir().join(&template_file);
+    let template_path = get_template_dir(project).join(&template_file);
 
-    // Extract problem letter (e.g., "1900A" -> "A") or use "Others"
-    let dir_name = match extract_problem_letter(name) {
-        Some(c) => format!("{}-set", c.to_ascii_uppercase()),
-        None => "Others".to_string(),
+    let layout = load_layout(settings);
+    let rating = if layout.needs_rating() {
+        let rating = fetch_problem_rating(settings, name);
+        if rating.is_none() {
+            eprintln!("No rating found for {}, using unrated", name);
+        }
+        rating
+    } else {
+        None
     };
 
-    let dir_path = Path::new("solutions").join(&dir_name);
-    fs::create_dir_all(&dir_path).expect("Failed to create directory");
-
-    let output_path = dir_path.join(format!("{}.{}", name, ext));
+    let output_path = project
+        .solutions_dir()
+        .join(layout.render(name, ext, rating));
+    if let Some(dir_path) = output_path.parent() {
+        create_dir(dir_path)?;
+    }
 
     if output_path.exists() {
-        eprintln!("File {} already exists!", output_path.display());
-        return;
+        return Err(format!("File {} already exists!", output_path.display()).into());
     }
 
-    match fs::read_to_string(&template_path) {
-        Ok(content) => {
-            fs::write(&output_path, content).expect("Failed to write file");
-            println!("Created: {}", output_path.display());
-        }
-        Err(_) => eprintln!("Template not found: {:?}", template_path),
+    let content = fs::read_to_string(&template_path)
+        .map_err(|_| format!("Template not found: {:?}", template_path))?;
+    write_file(&output_path, content)?;
+    println!("Created: {}", output_path.display());
+    if edit {
+        open_editor(settings, &output_path);
     }
+    Ok(())
 }
 
-fn count_solutions() -> std::collections::BTreeMap<String, usize> {
+/// Number of solutions per directory, e.g. `A-set` or `contests/1900`.
+fn count_solutions(settings: &Settings) -> std::collections::BTreeMap<String, usize> {
+    let project = settings.project();
     let mut counts = std::collections::BTreeMap::new();
-    let solutions_dir = Path::new("solutions");
-
-    if !solutions_dir.exists() {
-        return counts;
-    }
-
-    if let Ok(entries) = fs::read_dir(solutions_dir) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                let dir_name = entry.file_name().to_string_lossy().to_string();
-                let file_count = fs::read_dir(entry.path())
-                    .map(|e| {
-                        e.filter(|f| f.as_ref().map(|f| f.path().is_file()).unwrap_or(false))
-                            .count()
-                    })
-                    .unwrap_or(0);
-                if file_count > 0 {
-                    counts.insert(dir_name, file_count);
-                }
-            }
-        }
+    let solutions_dir = project.solutions_dir();
+    for (path, _, _) in list_solution_files(project, &load_layout(settings)) {
+        let dir = path
+            .parent()
+            .and_then(|p| p.strip_prefix(&solutions_dir).ok())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+        *counts.entry(dir).or_insert(0) += 1;
     }
     counts
 }
 
-fn list_templates() {
+fn list_templates(settings: &Settings, json: bool) -> Result<(), Error> {
+    let project = settings.project();
+    if json {
+        let counts = count_solutions(settings);
+        let result = serde_json::json!({
+            "languages": ["py", "cpp", "hs", "rs", "kt"],
+            "project": project.file,
+            "layout": load_layout(settings).pattern(),
+            "total": counts.values().sum::<usize>(),
+            "solutions": counts,
+        });
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        return Ok(());
+    }
+
     println!("Templates:");
     println!("  py, python  - Python");
     println!("  cpp, c++    - C++");
     println!("  hs, haskell - Haskell");
+    println!("  rs, rust    - Rust");
+    println!("  kt, kotlin  - Kotlin");
+    if let Some(file) = &project.file {
+        println!("\nProject: {}", file.display());
+    }
+    println!("\nLayout: solutions/{}", load_layout(settings).pattern());
 
-    let counts = count_solutions();
+    let counts = count_solutions(settings);
     if !counts.is_empty() {
         println!("\nSolutions:");
         let total: usize = counts.values().sum();
@@ -266,28 +582,39 @@ fn list_templates() {
         println!("  --------");
         println!("  Total: {}", total);
     }
+    Ok(())
 }
 
-fn get_samples_dir(name: &str) -> std::path::PathBuf {
-    Path::new("samples").join(name)
+fn create_dir(path: &Path) -> Result<(), Error> {
+    fs::create_dir_all(path)
+        .map_err(|e| Error::Other(format!("Failed to create {}: {}", path.display(), e)))
 }
 
-fn create_samples(name: &str, count: usize) {
-    let (contest_id, problem_letter) = match parse_problem_name(name) {
-        Some(p) => p,
-        None => {
-            eprintln!("Invalid problem name: {}. Use format like 1900A", name);
-            return;
-        }
-    };
+fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), Error> {
+    fs::write(path, contents)
+        .map_err(|e| Error::Other(format!("Failed to write {}: {}", path.display(), e)))
+}
 
-    let samples_dir = get_samples_dir(name);
-    fs::create_dir_all(&samples_dir).expect("Failed to create directory");
+fn get_samples_dir(project: &Project, name: &str) -> std::path::PathBuf {
+    project.samples_dir().join(name)
+}
 
-    let url = format!(
-        "https://codeforces.com/contest/{}/problem/{}",
-        contest_id, problem_letter
-    );
+fn create_samples(settings: &Settings, id: &ProblemId, count: usize) -> Result<(), Error> {
+    let samples_dir = get_samples_dir(settings.project(), &id.to_string());
+    create_dir(&samples_dir)?;
+
+    let url = id.problem_url(&settings.site());
+
+    match api_client(settings).page(&url) {
+        Ok(html) => {
+            let samples = samples::parse_samples(&html);
+            if !samples.is_empty() {
+                return save_samples(&samples_dir, &samples);
+            }
+            eprintln!("No samples found on {}", url);
+        }
+        Err(e) => eprintln!("Failed to download samples: {}", e),
+    }
 
     // Create empty sample files for manual input
     for i in 1..=count {
@@ -295,10 +622,10 @@ fn create_samples(name: &str, count: usize) {
         let ans_path = samples_dir.join(format!("ans{}.txt", i));
 
         if !in_path.exists() {
-            fs::write(&in_path, "").expect("Failed to create input file");
+            write_file(&in_path, "")?;
         }
         if !ans_path.exists() {
-            fs::write(&ans_path, "").expect("Failed to create answer file");
+            write_file(&ans_path, "")?;
         }
     }
 
@@ -310,75 +637,270 @@ fn create_samples(name: &str, count: usize) {
     println!("Opening: {}", url);
 
     // Open browser for copy-paste
-    #[cfg(target_os = "macos")]
-    let _ = Command::new("open").arg(&url).spawn();
-    #[cfg(target_os = "linux")]
-    let _ = Command::new("xdg-open").arg(&url).spawn();
-    #[cfg(target_os = "windows")]
-    let _ = Command::new("cmd").args(["/C", "start", &url]).spawn();
+    open_browser(settings, &url);
+    Ok(())
 }
 
-fn find_solution_file(name: &str, lang: &str) -> Option<std::path::PathBuf> {
-    let ext = match lang {
-        "py" | "python" => "py",
-        "cpp" | "c++" => "cpp",
-        "hs" | "haskell" => "hs",
-        _ => "py", // default to py
-    };
+fn save_samples(samples_dir: &Path, samples: &[samples::Sample]) -> Result<(), Error> {
+    println!("Created: {}", samples_dir.display());
+    for (i, sample) in samples.iter().enumerate() {
+        let in_path = samples_dir.join(format!("in{}.txt", i + 1));
+        let ans_path = samples_dir.join(format!("ans{}.txt", i + 1));
+        // Keep answers already filled in by hand
+        let is_empty = |p: &Path| fs::read_to_string(p).map_or(true, |s| s.trim().is_empty());
+        if is_empty(&in_path) && is_empty(&ans_path) {
+            write_file(&in_path, &sample.input)?;
+            write_file(&ans_path, &sample.output)?;
+            println!("  in{}.txt, ans{}.txt", i + 1, i + 1);
+        } else {
+            println!("  in{}.txt, ans{}.txt (kept existing)", i + 1, i + 1);
+        }
+    }
+    Ok(())
+}
+
+fn find_solution_file(settings: &Settings, name: &str, lang: &str) -> Option<PathBuf> {
+    let project = settings.project();
+    let ext = lang_ext(lang).unwrap_or("py");
+    let layout = load_layout(settings);
+
+    // Preferred extension first, then the others as fallback
+    let mut exts = vec![ext];
+    exts.extend(
+        ["py", "cpp", "hs", "rs", "kt"]
+            .into_iter()
+            .filter(|e| *e != ext),
+    );
+
+    if layout.needs_rating() {
+        // The rating in the path may have changed since, so search instead
+        let files = list_solution_files(project, &layout);
+        return exts.iter().find_map(|ext| {
+            files
+                .iter()
+                .find(|(_, n, e)| n == name && e == ext)
+                .map(|(path, _, _)| path.clone())
+        });
+    }
 
-    let letter = extract_problem_letter(name)?.to_ascii_uppercase();
-    let dir = Path::new("solutions").join(format!("{}-set", letter));
+    exts.iter()
+        .map(|ext| project.solutions_dir().join(layout.render(name, ext, None)))
+        .find(|path| path.exists())
+}
 
-    let path = dir.join(format!("{}.{}", name, ext));
-    if path.exists() {
-        return Some(path);
+/// Runs the solution on the samples, failing unless all of them passed.
+/// With `json`, prints a JSON object with the result of each sample instead
+/// of text. With `report`, also writes a JUnit XML report there.
+fn test_solution(
+    settings: &Settings,
+    name: &str,
+    num: Option<usize>,
+    lang: &str,
+    checker: Checker,
+    json: bool,
+    report: Option<&Path>,
+) -> Result<(), Error> {
+    let solution = find_solution_file(settings, name, lang)
+        .ok_or_else(|| format!("No solution file found for {}", name))?;
+
+    let run = run_samples(settings, name, &solution, num, checker, !json);
+    if json {
+        let result = serde_json::json!({
+            "name": run.name,
+            "solution": run.solution,
+            "checker": checker.to_string(),
+            "passed": run.passed(),
+            "failed": run.failed(),
+            "tests": run.tests,
+            "error": run.error,
+        });
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+    } else if run.error.is_none() {
+        println!(
+            "\nResults: {} passed, {} failed",
+            run.passed(),
+            run.failed()
+        );
     }
+    if let Some(path) = report {
+        write_report(path, std::slice::from_ref(&run))?;
+    }
+    match run.error {
+        Some(error) if run.compilation_failed => Err(Error::Compilation(error)),
+        Some(error) => Err(error.into()),
+        None if run.ok() => Ok(()),
+        None => Err(Error::TestsFailed(String::new())),
+    }
+}
 
-    // Fallback: try other extensions
-    for fallback_ext in &["py", "cpp", "hs"] {
-        if *fallback_ext == ext {
+/// Order of languages in the summary grid of `cf test --all`.
+const LANG_ORDER: [&str; 5] = ["py", "cpp", "rs", "hs", "kt"];
+
+/// Tests every solution of a problem with samples, in each language present
+/// (or only `lang`), printing a grid of problems by languages. Fails unless
+/// all of them passed.
+fn test_all(
+    settings: &Settings,
+    lang: Option<&str>,
+    checker: Checker,
+    json: bool,
+    report: Option<&Path>,
+) -> Result<(), Error> {
+    let project = settings.project();
+    let only = lang.map(|l| lang_ext(l).unwrap_or(l));
+    let mut solutions = Vec::new();
+    let mut no_samples = 0;
+    for (path, name, ext) in list_solution_files(project, &load_layout(settings)) {
+        if only.is_some_and(|only| only != ext) {
             continue;
         }
-        let path = dir.join(format!("{}.{}", name, fallback_ext));
-        if path.exists() {
-            return Some(path);
+        if get_samples_dir(project, &name).is_dir() {
+            solutions.push((name, ext, path));
+        } else {
+            no_samples += 1;
         }
     }
-    None
+    solutions.sort();
+
+    let color = use_color(settings);
+    let mut runs = Vec::new();
+    for (name, _, solution) in &solutions {
+        let run = run_samples(settings, name, solution, None, checker, false);
+        if !json {
+            let status = match &run.error {
+                Some(error) => paint(color, RED, error.lines().next().unwrap_or("")),
+                None => paint(
+                    color,
+                    if run.ok() { GREEN } else { RED },
+                    &format!("{}/{} passed", run.passed(), run.tests.len()),
+                ),
+            };
+            println!("{:<10} {:<4} {}", name, run.lang, status);
+        }
+        runs.push(run);
+    }
+
+    let failed = runs.iter().filter(|r| !r.ok()).count();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&runs).unwrap());
+    } else if !runs.is_empty() {
+        print_grid(&runs, color);
+        println!(
+            "\nSolutions: {} passed, {} failed",
+            runs.len() - failed,
+            failed
+        );
+    }
+    if no_samples > 0 && !json {
+        println!("Skipped {} solutions without samples", no_samples);
+    }
+    if let Some(path) = report {
+        write_report(path, &runs)?;
+    }
+    if runs.is_empty() {
+        return Err(Error::Other("No solutions with samples found".to_string()));
+    }
+    if failed > 0 {
+        return Err(Error::TestsFailed(String::new()));
+    }
+    Ok(())
 }
 
-fn test_solution(name: &str, num: Option<usize>, lang: &str) {
-    let solution = match find_solution_file(name, lang) {
-        Some(p) => p,
-        None => {
-            eprintln!("No solution file found for {}", name);
-            return;
+/// Prints pass/fail of each problem (rows) in each language (columns).
+fn print_grid(runs: &[TestRun], color: bool) {
+    let mut langs: Vec<&str> = runs.iter().map(|r| r.lang.as_str()).collect();
+    langs.sort_by_key(|l| {
+        LANG_ORDER
+            .iter()
+            .position(|o| o == l)
+            .unwrap_or(LANG_ORDER.len())
+    });
+    langs.dedup();
+    let mut problems: Vec<&str> = runs.iter().map(|r| r.name.as_str()).collect();
+    problems.dedup();
+
+    let mut header = format!("{:<10}", "Problem");
+    for lang in &langs {
+        header.push_str(&format!(" {:<5}", lang));
+    }
+    println!("\n{}", header.trim_end());
+    for problem in problems {
+        let mut row = format!("{:<10}", problem);
+        for lang in &langs {
+            let run = runs.iter().find(|r| r.name == problem && r.lang == *lang);
+            // Pad before painting, escape codes have no width
+            let cell = match run {
+                Some(run) if run.ok() => paint(color, GREEN, "pass "),
+                Some(_) => paint(color, RED, "FAIL "),
+                None => "-    ".to_string(),
+            };
+            row.push(' ');
+            row.push_str(&cell);
         }
+        println!("{}", row.trim_end());
+    }
+}
+
+fn write_report(path: &Path, runs: &[TestRun]) -> Result<(), Error> {
+    write_file(path, report::junit(runs))?;
+    eprintln!("Report: {}", path.display());
+    Ok(())
+}
+
+/// Runs a solution on the samples of `name`. With `verbose`, prints each
+/// sample's result as it finishes.
+fn run_samples(
+    settings: &Settings,
+    name: &str,
+    solution: &Path,
+    num: Option<usize>,
+    checker: Checker,
+    verbose: bool,
+) -> TestRun {
+    let ext = solution.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let mut run = TestRun {
+        name: name.to_string(),
+        solution: solution.to_path_buf(),
+        lang: ext.to_string(),
+        tests: Vec::new(),
+        error: None,
+        compilation_failed: false,
     };
 
-    let samples_dir = get_samples_dir(name);
+    let samples_dir = get_samples_dir(settings.project(), name);
     if !samples_dir.exists() {
-        println!("No samples found. Run: cf eg {}", name);
-        return;
+        run.error = Some(format!("No samples found. Run: cf eg {}", name));
+        return run;
     }
 
-    let ext = solution.extension().and_then(|s| s.to_str()).unwrap_or("");
-    println!("Testing: {}", solution.display());
+    if verbose {
+        if checker == Checker::default() {
+            println!("Testing: {}", solution.display());
+        } else {
+            println!("Testing: {} (checker: {})", solution.display(), checker);
+        }
+    }
 
-    let cpp_exe = if ext == "cpp" {
-        match compile_cpp(&solution) {
-            Ok(exe) => Some(exe),
-            Err(err) => {
-                eprintln!("Compilation failed:\n{}", err.trim_end());
-                return;
-            }
+    let compiled = match ext {
+        "cpp" => Some(compile_cpp(solution)),
+        "rs" => {
+            Some(bundle_rust(settings.project(), solution, name).and_then(|src| compile_rust(&src)))
         }
-    } else {
-        None
+        "kt" => Some(compile_kotlin(solution)),
+        _ => None,
+    };
+    let exe = match compiled {
+        Some(Ok(exe)) => Some(exe),
+        Some(Err(err)) => {
+            run.error = Some(format!("Compilation failed:\n{}", err.trim_end()));
+            run.compilation_failed = true;
+            return run;
+        }
+        None => None,
     };
 
-    let mut passed = 0;
-    let mut failed = 0;
+    let color = use_color(settings);
+    let time_limit = BASE_TIME_LIMIT.mul_f64(settings.time_limit_factor());
 
     // If specific test number, only run that one
     let test_nums: Vec<usize> = if let Some(n) = num {
@@ -395,8 +917,8 @@ fn test_solution(name: &str, num: Option<usize>, lang: &str) {
     };
 
     if test_nums.is_empty() {
-        eprintln!("No test cases found. Run: cf eg {}", name);
-        return;
+        run.error = Some(format!("No test cases found. Run: cf eg {}", name));
+        return run;
     }
 
     for test_num in &test_nums {
@@ -412,39 +934,68 @@ fn test_solution(name: &str, num: Option<usize>, lang: &str) {
             continue;
         }
 
-        let expected = fs::read_to_string(&ans_path)
-            .unwrap_or_default()
-            .trim()
-            .to_string();
+        let expected = fs::read_to_string(&ans_path).unwrap_or_default();
 
-        let output = run_solution(&solution, ext, &in_path, cpp_exe.as_deref());
-
-        let actual = output.trim().to_string();
-        if actual == expected {
-            println!("Test {}: PASSED", test_num);
-            passed += 1;
+        let result = run_solution(solution, ext, &in_path, exe.as_deref(), time_limit);
+        let verdict = if result.timed_out {
+            "time_limit_exceeded"
+        } else if checker.check(&expected, &result.stdout) {
+            "passed"
         } else {
-            println!("Test {}: FAILED", test_num);
-            println!("  Expected: {}", expected.replace('\n', "\\n"));
-            println!("  Got:      {}", actual.replace('\n', "\\n"));
-            failed += 1;
+            "failed"
+        };
+
+        if verbose {
+            match verdict {
+                "time_limit_exceeded" => println!(
+                    "Test {}: {} (> {:.1}s)",
+                    test_num,
+                    paint(color, YELLOW, "TIME LIMIT EXCEEDED"),
+                    time_limit.as_secs_f64()
+                ),
+                "passed" => println!("Test {}: {}", test_num, paint(color, GREEN, "PASSED")),
+                _ => {
+                    println!("Test {}: {}", test_num, paint(color, RED, "FAILED"));
+                    println!("  Expected: {}", expected.trim().replace('\n', "\\n"));
+                    println!("  Got:      {}", result.stdout.trim().replace('\n', "\\n"));
+                }
+            }
         }
+        run.tests.push(TestCase {
+            test: *test_num,
+            verdict,
+            time_ms: result.elapsed.as_millis(),
+            expected,
+            output: result.stdout,
+        });
     }
 
     // Clean up compiled executable
-    if let Some(exe) = cpp_exe {
+    if let Some(exe) = exe {
         let _ = fs::remove_file(&exe);
     }
+    run
+}
 
-    println!("\nResults: {} passed, {} failed", passed, failed);
+fn bundle_rust(project: &Project, solution: &Path, name: &str) -> Result<PathBuf, String> {
+    bundle::bundle_solution(
+        solution,
+        name,
+        &project.library_dir(),
+        &project.target_dir().join("bundle"),
+    )
 }
 
-fn compile_cpp(source: &Path) -> Result<PathBuf, String> {
-    let exe = if std::env::consts::EXE_EXTENSION.is_empty() {
+fn exe_path(source: &Path) -> PathBuf {
+    if std::env::consts::EXE_EXTENSION.is_empty() {
         source.with_extension("")
     } else {
         source.with_extension(std::env::consts::EXE_EXTENSION)
-    };
+    }
+}
+
+fn compile_cpp(source: &Path) -> Result<PathBuf, String> {
+    let exe = exe_path(source);
 
     let output = Command::new("g++")
         .args(["-std=gnu++23", "-O2", "-pipe", "-o"])
@@ -465,243 +1016,1303 @@ fn compile_cpp(source: &Path) -> Result<PathBuf, String> {
     Ok(exe)
 }
 
-fn run_solution(path: &Path, ext: &str, input_path: &Path, cpp_exe: Option<&Path>) -> String {
+fn compile_rust(source: &Path) -> Result<PathBuf, String> {
+    let exe = exe_path(source);
+
+    let output = Command::new("rustc")
+        .args(["--edition", "2021", "-O", "-o"])
+        .arg(&exe)
+        .arg(source)
+        .output()
+        .map_err(|e| format!("Failed to run rustc: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        return Err(if stderr.trim().is_empty() {
+            "rustc failed (no stderr captured)".to_string()
+        } else {
+            stderr
+        });
+    }
+
+    Ok(exe)
+}
+
+fn compile_kotlin(source: &Path) -> Result<PathBuf, String> {
+    let jar = source.with_extension("jar");
+
+    let output = Command::new("kotlinc")
+        .arg(source)
+        .args(["-include-runtime", "-d"])
+        .arg(&jar)
+        .output()
+        .map_err(|e| format!("Failed to run kotlinc: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        return Err(if stderr.trim().is_empty() {
+            "kotlinc failed (no stderr captured)".to_string()
+        } else {
+            stderr
+        });
+    }
+
+    Ok(jar)
+}
+
+const GREEN: &str = "32";
+const RED: &str = "31";
+const YELLOW: &str = "33";
+const CYAN: &str = "36";
+
+fn use_color(settings: &Settings) -> bool {
+    match settings.color() {
+        Color::Always => true,
+        Color::Never => false,
+        Color::Auto => std::env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal(),
+    }
+}
+
+/// Wraps `text` in an ANSI color code when colors are enabled.
+fn paint(enabled: bool, code: &str, text: &str) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+struct RunResult {
+    stdout: String,
+    timed_out: bool,
+    elapsed: Duration,
+}
+
+fn run_solution(
+    path: &Path,
+    ext: &str,
+    input_path: &Path,
+    exe: Option<&Path>,
+    time_limit: Duration,
+) -> RunResult {
     let input = fs::read_to_string(input_path).unwrap_or_default();
 
-    let output = match ext {
-        "py" => Command::new("python3")
-            .arg(path)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .and_then(|mut child| {
-                use std::io::Write;
-                if let Some(stdin) = child.stdin.as_mut() {
-                    let _ = stdin.write_all(input.as_bytes());
-                }
-                child.wait_with_output()
-            }),
-        "cpp" => {
-            let exe = match cpp_exe {
-                Some(p) => p,
-                None => return "Missing compiled executable".to_string(),
-            };
-            Command::new(exe)
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-                .and_then(|mut child| {
-                    use std::io::Write;
-                    if let Some(stdin) = child.stdin.as_mut() {
-                        let _ = stdin.write_all(input.as_bytes());
-                    }
-                    child.wait_with_output()
-                })
-        }
-        "hs" => Command::new("runhaskell")
-            .arg(path)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .and_then(|mut child| {
-                use std::io::Write;
-                if let Some(stdin) = child.stdin.as_mut() {
-                    let _ = stdin.write_all(input.as_bytes());
-                }
-                child.wait_with_output()
-            }),
-        _ => return "Unknown language".to_string(),
+    let command = match ext {
+        "py" => {
+            let mut cmd = Command::new("python3");
+            cmd.arg(path);
+            cmd
+        }
+        "cpp" | "rs" => match exe {
+            Some(exe) => Command::new(exe),
+            None => return RunResult::error("Missing compiled executable".to_string()),
+        },
+        "hs" => {
+            let mut cmd = Command::new("runhaskell");
+            cmd.arg(path);
+            cmd
+        }
+        "kt" => match exe {
+            Some(jar) => {
+                let mut cmd = Command::new("java");
+                cmd.arg("-jar").arg(jar);
+                cmd
+            }
+            None => return RunResult::error("Missing compiled jar".to_string()),
+        },
+        _ => return RunResult::error("Unknown language".to_string()),
     };
 
-    match output {
-        Ok(o) => String::from_utf8_lossy(&o.stdout).to_string(),
-        Err(e) => format!("Error: {}", e),
+    run_with_limit(command, input, time_limit)
+        .unwrap_or_else(|e| RunResult::error(format!("Error: {}", e)))
+}
+
+impl RunResult {
+    fn error(message: String) -> RunResult {
+        RunResult {
+            stdout: message,
+            timed_out: false,
+            elapsed: Duration::ZERO,
+        }
     }
 }
 
-fn login() {
-    print!("CF Handle: ");
-    io::stdout().flush().unwrap();
-    let mut handle = String::new();
-    io::stdin().read_line(&mut handle).unwrap();
-    let handle = handle.trim().to_string();
+/// Runs `command` with `input` on stdin, killing it after `time_limit`.
+fn run_with_limit(
+    mut command: Command,
+    input: String,
+    time_limit: Duration,
+) -> io::Result<RunResult> {
+    let start = Instant::now();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Feed stdin and drain stdout on threads so neither side can block
+    let stdin = child.stdin.take();
+    let writer = std::thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut stdout) = stdout {
+            let _ = stdout.read_to_end(&mut buf);
+        }
+        buf
+    });
+
+    let timed_out = loop {
+        if child.try_wait()?.is_some() {
+            break false;
+        }
+        if start.elapsed() > time_limit {
+            let _ = child.kill();
+            let _ = child.wait();
+            break true;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    };
+    let elapsed = start.elapsed();
+
+    let _ = writer.join();
+    let stdout = reader.join().unwrap_or_default();
+    Ok(RunResult {
+        stdout: String::from_utf8_lossy(&stdout).to_string(),
+        timed_out,
+        elapsed,
+    })
+}
+
+fn login(settings: &Settings) -> Result<(), Error> {
+    let handle = prompt("CF Handle");
 
     if handle.is_empty() {
-        eprintln!("Handle cannot be empty");
-        return;
+        return Err(Error::Other("Handle cannot be empty".to_string()));
     }
 
     println!("Verifying handle {}...", handle);
 
     // Verify handle exists via CF API
-    let url = format!("https://codeforces.com/api/user.info?handles={}", handle);
-    let client = reqwest::blocking::Client::new();
-
-    match client.get(&url).send() {
-        Ok(resp) => {
-            let text = resp.text().unwrap_or_default();
-            if text.contains("\"status\":\"OK\"") {
-                println!("Handle verified! Saved.");
-                let mut config = load_config();
-                config.handle = Some(handle);
-                save_config(&config);
-            } else {
-                eprintln!("Handle not found on Codeforces");
-            }
+    let users = match api_client(settings).user_info(&[&handle]) {
+        Ok(users) => users,
+        Err(api::ApiError::Failed(_)) => {
+            return Err(Error::Other("Handle not found on Codeforces".to_string()))
         }
-        Err(e) => eprintln!("Failed to verify: {}", e),
+        Err(e) => return Err(Error::Network(format!("Failed to verify: {}", e))),
+    };
+    // The API corrects the handle's capitalization
+    let handle = users.into_iter().next().map_or(handle, |u| u.handle);
+    let mut config = settings.config.clone();
+    match config.active_profile() {
+        Some(profile) => println!("Handle verified! Saved to profile {}.", profile),
+        None => println!("Handle verified! Saved."),
+    }
+    let account = config.account_mut();
+    if account.handle.as_deref() != Some(handle.as_str()) {
+        // Cookies belong to the previous handle's session
+        *account.cookies = None;
     }
+    *account.handle = Some(handle.clone());
+    save_config(&config);
+    login_web(settings, &handle)
 }
 
-fn watch(name: Option<String>, ac_only: bool) {
-    let config = load_config();
-    let handle = match config.handle {
-        Some(h) => h,
-        None => {
-            eprintln!("Not logged in. Run: cf login");
-            return;
+/// Logs in on the website with a password so `cf submit` can submit
+/// directly. Only the session cookies are saved, never the password.
+fn login_web(settings: &Settings, handle: &str) -> Result<(), Error> {
+    let password = prompt_secret("Password (empty to skip, submit will use the browser)");
+    if password.is_empty() {
+        return Ok(());
+    }
+
+    let session = session::Session::new(&settings.site(), settings.timeout(), None)?;
+    let handle = match session.login(handle, &password) {
+        Ok(handle) => handle,
+        Err(session::SessionError::NotLoggedIn) => {
+            return Err(Error::NotLoggedIn("Wrong handle or password".to_string()))
         }
+        Err(e) => return Err(Error::Network(format!("Failed to log in: {}", e))),
     };
+    // Reloaded since login just saved the handle
+    let mut config = settings.reload().config;
+    *config.account_mut().cookies = session.cookies();
+    save_config(&config);
+    println!("Logged in as {}.", handle);
+    Ok(())
+}
 
-    println!("Fetching submissions for {}...", handle);
+fn prompt(label: &str) -> String {
+    print!("{}: ", label);
+    io::stdout().flush().unwrap();
+    let mut line = String::new();
+    io::stdin().read_line(&mut line).unwrap();
+    line.trim().to_string()
+}
 
-    let url = format!(
-        "https://codeforces.com/api/user.status?handle={}&from=1&count=100",
-        handle
-    );
-    let client = reqwest::blocking::Client::new();
+/// Reads a line without echoing it when run interactively.
+fn prompt_secret(label: &str) -> String {
+    if io::stdin().is_terminal() {
+        rpassword::prompt_password(format!("{}: ", label)).unwrap_or_default()
+    } else {
+        prompt(label)
+    }
+}
 
-    let resp = match client.get(&url).send() {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("Failed to fetch: {}", e);
-            return;
-        }
-    };
+fn login_api_key(settings: &Settings) -> Result<(), Error> {
+    println!("Create a key at https://codeforces.com/settings/api");
+    let key = prompt("API key");
+    let secret = prompt("API secret");
+    if key.is_empty() || secret.is_empty() {
+        return Err(Error::Other("Key and secret cannot be empty".to_string()));
+    }
 
-    let text = resp.text().unwrap_or_default();
-    let json: serde_json::Value = match serde_json::from_str(&text) {
-        Ok(j) => j,
-        Err(_) => {
-            eprintln!("Failed to parse response");
-            return;
-        }
+    println!("Verifying key...");
+    let credentials = api::Credentials {
+        key: key.clone(),
+        secret: secret.clone(),
     };
+    let client =
+        api::Client::new(&settings.site(), settings.timeout()).with_credentials(Some(credentials));
+    if let Err(e) = client.user_friends(false) {
+        return Err(Error::Network(format!("Failed to verify: {}", e)));
+    }
 
-    if json["status"] != "OK" {
-        eprintln!("API error: {}", json["comment"]);
-        return;
+    let mut config = settings.config.clone();
+    let account = config.account_mut();
+    *account.api_key = Some(key);
+    *account.api_secret = Some(secret);
+    save_config(&config);
+    match config.active_profile() {
+        Some(profile) => println!("Key verified! Saved to profile {}.", profile),
+        None => println!("Key verified! Saved."),
     }
+    Ok(())
+}
 
-    let submissions = match json["result"].as_array() {
-        Some(s) => s,
-        None => {
-            eprintln!("No submissions found");
-            return;
+fn friends(settings: &Settings, only_online: bool) -> Result<(), Error> {
+    let client = api_client(settings);
+    let handles = client.user_friends(only_online)?;
+    if handles.is_empty() {
+        println!("No friends found");
+        return Ok(());
+    }
+
+    let handles: Vec<&str> = handles.iter().map(String::as_str).collect();
+    match client.user_info(&handles) {
+        Ok(mut users) => {
+            users.sort_by_key(|u| std::cmp::Reverse(u.rating));
+            for user in users {
+                let rating = user.rating.map_or("unrated".to_string(), |r| r.to_string());
+                println!(
+                    "{:<24} {:>7}  {}",
+                    user.handle,
+                    rating,
+                    user.rank.as_deref().unwrap_or("")
+                );
+            }
         }
+        Err(e) => {
+            eprintln!("{}", e);
+            for handle in handles {
+                println!("{}", handle);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handle of the active account, failing with how to log in without one.
+fn active_handle(settings: &Settings) -> Result<String, Error> {
+    settings.handle().ok_or_else(|| {
+        Error::NotLoggedIn(match settings.config.active_profile() {
+            Some(profile) => format!(
+                "Not logged in with profile {}. Run: cf login --profile {}",
+                profile, profile
+            ),
+            None => "Not logged in. Run: cf login".to_string(),
+        })
+    })
+}
+
+/// Submissions fetched per API call when paging through history.
+const PAGE_SIZE: usize = 100;
+
+/// Color of a verdict in listings.
+fn verdict_color(verdict: Option<&str>) -> &'static str {
+    match verdict {
+        Some("OK") => GREEN,
+        None | Some("TESTING") => CYAN,
+        Some("TIME_LIMIT_EXCEEDED" | "MEMORY_LIMIT_EXCEEDED" | "IDLENESS_LIMIT_EXCEEDED") => YELLOW,
+        _ => RED,
+    }
+}
+
+/// Fetches the submissions to show, newest first, and whether more may match
+/// beyond `--limit` or the searched pages. Paging stops after `max_pages`
+/// API calls.
+fn fetch_watched(
+    client: &api::Client,
+    handle: &str,
+    args: &WatchArgs,
+    now: i64,
+    max_pages: usize,
+) -> Result<(Vec<api::Submission>, bool), api::ApiError> {
+    let name = &args.name;
+    // Scope the request to the contest when one is given, which is also the
+    // only way to reach gym submissions of a specific gym contest.
+    let contest_filter = name.as_deref().and_then(|n| {
+        n.parse::<u64>().ok().or_else(|| {
+            n.parse::<ProblemId>()
+                .ok()
+                .filter(|id| id.kind != ProblemKind::Acmsguru)
+                .map(|id| id.contest)
+        })
+    });
+    let cutoff = args.since.map(|since| now - since);
+    let mut verdicts = args.verdict.clone();
+    if args.ac {
+        verdicts.push("OK".to_string());
+    }
+    let filter = filter::Filter {
+        name: name.clone(),
+        verdicts,
+        lang: args.lang.clone(),
+        tags: args.tag.clone(),
+        rating: args.rating,
+        contest: args.contest,
     };
 
-    let mut shown = 0;
+    let mut shown = Vec::new();
+    let mut from = 1;
+    // Newest first, so paging stops at the limit or the `--since` cutoff
+    for _ in 0..max_pages {
+        let page = match contest_filter {
+            Some(contest_id) => client.contest_status(contest_id, handle, from, PAGE_SIZE)?,
+            None => client.user_status(handle, from, PAGE_SIZE)?,
+        };
+        let last_page = page.len() < PAGE_SIZE;
+
+        for sub in page {
+            if cutoff.is_some_and(|cutoff| sub.creation_time_seconds < cutoff) {
+                return Ok((shown, false));
+            }
+            if args.gym && sub.contest_id.unwrap_or(0) < GYM_CONTEST_MIN {
+                continue;
+            }
+            if !filter.matches(&sub) {
+                continue;
+            }
 
+            if args.limit > 0 && shown.len() == args.limit {
+                return Ok((shown, true));
+            }
+            shown.push(sub);
+        }
+        if last_page {
+            return Ok((shown, false));
+        }
+        from += PAGE_SIZE;
+    }
+    Ok((shown, true))
+}
+
+/// Prints one row per submission, in reverse video if its ID is in
+/// `changed`.
+fn print_watched(submissions: &[api::Submission], now: i64, color: bool, changed: &HashSet<u64>) {
     for sub in submissions {
-        let verdict = sub["verdict"].as_str().unwrap_or("");
-        if ac_only && verdict != "OK" {
-            continue;
+        let verdict = paint(
+            color,
+            verdict_color(sub.verdict.as_deref()),
+            &format!("{:<18}", sub.verdict_short()),
+        );
+        let row = format!(
+            "{:<10} | {:>8} | {:<8} | {:<20} | {} | {:>5} ms | {:>7} KB",
+            sub.id,
+            format!("{} ago", age::format(now - sub.creation_time_seconds)),
+            sub.problem.short_name(),
+            sub.programming_language,
+            verdict,
+            sub.time_consumed_millis,
+            sub.memory_consumed_bytes / 1024
+        );
+        if changed.contains(&sub.id) {
+            println!("{}", paint(color, "7", &row));
+        } else {
+            println!("{}", row);
         }
+    }
+}
 
-        let contest_id = sub["contestId"].as_u64().unwrap_or(0);
-        let problem_idx = sub["problem"]["index"].as_str().unwrap_or("");
-        let problem_name = format!("{}{}", contest_id, problem_idx);
-
-        // Filter by name if provided
-        if let Some(ref filter) = name {
-            let matches = problem_name
-                .to_lowercase()
-                .starts_with(&filter.to_lowercase())
-                || filter
-                    .parse::<u64>()
-                    .map(|id| contest_id == id)
-                    .unwrap_or(false);
-            if !matches {
+fn watch(settings: &Settings, args: &WatchArgs, json: bool) -> Result<(), Error> {
+    let handle = active_handle(settings)?;
+    if args.follow {
+        follow(settings, &handle, args, json);
+        return Ok(());
+    }
+
+    if !json {
+        println!("Fetching submissions for {}...", handle);
+    }
+    let client = api_client(settings);
+    let now = age::now();
+    let max_pages = if args.pages == 0 {
+        usize::MAX
+    } else {
+        args.pages
+    };
+    let (shown, more) = fetch_watched(&client, &handle, args, now, max_pages)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&shown).unwrap());
+        return Ok(());
+    }
+
+    let color = use_color(settings);
+    print_watched(&shown, now, color, &HashSet::new());
+
+    if shown.is_empty() {
+        println!("No matching submissions found");
+    }
+    if more && args.limit > 0 && shown.len() == args.limit {
+        println!(
+            "... (showing the latest {}, use --limit for more)",
+            shown.len()
+        );
+    } else if more {
+        println!(
+            "... (searched the latest {} submissions, use --pages for more)",
+            max_pages * PAGE_SIZE
+        );
+    }
+    Ok(())
+}
+
+/// Shortest `--interval`, matching the API client's spacing of calls.
+const MIN_FOLLOW_INTERVAL: u64 = 2;
+
+/// Refreshes the list until interrupted. On a terminal the screen is
+/// redrawn with changed rows highlighted; otherwise new and changed
+/// submissions are printed as they appear. With `json`, those are printed
+/// one JSON object per line.
+fn follow(settings: &Settings, handle: &str, args: &WatchArgs, json: bool) {
+    let client = api_client(settings);
+    let color = use_color(settings);
+    let live = io::stdout().is_terminal();
+    let interval = Duration::from_secs(args.interval.max(MIN_FOLLOW_INTERVAL));
+    // Last seen verdict of each submission, `None` before the first poll
+    let mut seen: Option<HashMap<u64, String>> = None;
+
+    loop {
+        let now = age::now();
+        // Only the first page, to keep to the API's rate limit
+        let shown = match fetch_watched(&client, handle, args, now, 1) {
+            Ok((shown, _)) => shown,
+            Err(e) => {
+                eprintln!("{}", e);
+                thread::sleep(interval);
                 continue;
             }
+        };
+        let verdicts: HashMap<u64, String> =
+            shown.iter().map(|s| (s.id, s.verdict_short())).collect();
+        let changed: HashSet<u64> = match &seen {
+            Some(seen) => verdicts
+                .iter()
+                .filter(|(id, verdict)| seen.get(id) != Some(verdict))
+                .map(|(id, _)| *id)
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        if json {
+            for sub in &shown {
+                if seen.is_none() || changed.contains(&sub.id) {
+                    println!("{}", serde_json::to_string(sub).unwrap());
+                }
+            }
+        } else if live {
+            print!("\x1b[H\x1b[2J");
+            println!(
+                "Submissions of {} (every {}s, Ctrl-C to stop)",
+                handle,
+                interval.as_secs()
+            );
+            print_watched(&shown, now, color, &changed);
+            if shown.is_empty() {
+                println!("No matching submissions found");
+            }
+        } else if seen.is_none() {
+            print_watched(&shown, now, color, &changed);
+        } else {
+            let rows: Vec<api::Submission> = shown
+                .iter()
+                .filter(|s| changed.contains(&s.id))
+                .cloned()
+                .collect();
+            print_watched(&rows, now, color, &HashSet::new());
         }
+        let _ = io::stdout().flush();
 
-        let lang = sub["programmingLanguage"].as_str().unwrap_or("");
-        let status = if verdict == "OK" { "AC" } else { verdict };
+        seen = Some(verdicts);
+        thread::sleep(interval);
+    }
+}
 
-        println!("{:<10} | {:<20} | {}", problem_name, lang, status);
+fn stats(settings: &Settings, handle: Option<String>, json: bool) -> Result<(), Error> {
+    let handle = match handle {
+        Some(handle) => handle,
+        None => active_handle(settings)?,
+    };
 
-        shown += 1;
-        if shown >= 20 {
-            println!("... (showing first 20)");
-            break;
+    if !json {
+        println!("Fetching stats for {}...", handle);
+    }
+    let client = api_client(settings);
+    let user = client
+        .user_info(&[&handle])?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Other(format!("User {} not found", handle)))?;
+    let changes = client.user_rating(&user.handle).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        Vec::new()
+    });
+    let submissions = client.user_status_all(&user.handle)?;
+
+    // Solved problems by rating, and attempted ones never solved
+    let mut solved = std::collections::BTreeMap::new();
+    let mut attempted = std::collections::BTreeSet::new();
+    for sub in &submissions {
+        let key = sub.problem.short_name();
+        if sub.verdict.as_deref() == Some("OK") {
+            solved.insert(key, sub.problem.rating);
+        } else {
+            attempted.insert(key);
         }
     }
+    attempted.retain(|k| !solved.contains_key(k));
 
-    if shown == 0 {
-        println!("No matching submissions found");
+    let mut by_rating = std::collections::BTreeMap::new();
+    for rating in solved.values() {
+        *by_rating.entry(*rating).or_insert(0) += 1;
     }
-}
 
-fn submit(name: &str) {
-    let solution = match find_solution_file(name, "py") {
-        Some(p) => p,
-        None => {
-            eprintln!("No solution file found for {}", name);
-            return;
+    if json {
+        let by_rating: serde_json::Map<String, serde_json::Value> = by_rating
+            .iter()
+            .map(|(rating, count)| {
+                let key = rating.map_or("unrated".to_string(), |r| r.to_string());
+                (key, (*count).into())
+            })
+            .collect();
+        let result = serde_json::json!({
+            "handle": user.handle,
+            "rating": user.rating,
+            "rank": user.rank,
+            "max_rating": user.max_rating,
+            "max_rank": user.max_rank,
+            "contests": changes.len(),
+            "last_contest": changes.last().map(|last| serde_json::json!({
+                "contest_id": last.contest_id,
+                "name": last.contest_name,
+                "rank": last.rank,
+                "rating_change": last.new_rating - last.old_rating,
+            })),
+            "submissions": submissions.len(),
+            "solved": solved.len(),
+            "attempted_unsolved": attempted.len(),
+            "solved_by_rating": by_rating,
+        });
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        return Ok(());
+    }
+
+    match user.rating {
+        Some(rating) => println!(
+            "Rating: {} ({}), max {} ({})",
+            rating,
+            user.rank.as_deref().unwrap_or(""),
+            user.max_rating.unwrap_or(rating),
+            user.max_rank.as_deref().unwrap_or("")
+        ),
+        None => println!("Rating: unrated"),
+    }
+    if let Some(last) = changes.last() {
+        println!(
+            "Contests: {}, last: {} (rank {}, {:+})",
+            changes.len(),
+            last.contest_name,
+            last.rank,
+            last.new_rating - last.old_rating
+        );
+    }
+
+    println!("Submissions: {}", submissions.len());
+    println!("Solved: {}", solved.len());
+    println!("Attempted, unsolved: {}", attempted.len());
+
+    if !by_rating.is_empty() {
+        println!("\nSolved by rating:");
+        for (rating, count) in &by_rating {
+            match rating {
+                Some(r) => println!("  {:>7}: {}", r, count),
+                None => println!("  {:>7}: {}", "unrated", count),
+            }
         }
+    }
+    Ok(())
+}
+
+fn standings(settings: &Settings, contest: u64, limit: usize) -> Result<(), Error> {
+    let standings = api_client(settings).contest_standings(contest, 1, limit)?;
+    println!("{}", standings.contest.name);
+    if standings.rows.is_empty() {
+        println!("No participants yet");
+        return Ok(());
+    }
+    println!(
+        "\n{:>5}  {:<32} {:>7} {:>7}",
+        "Rank", "Who", "Points", "Penalty"
+    );
+    for row in &standings.rows {
+        println!(
+            "{:>5}  {:<32} {:>7} {:>7}",
+            row.rank,
+            row.party.name(),
+            row.points,
+            row.penalty
+        );
+    }
+    Ok(())
+}
+
+fn list_profiles(settings: &Settings) -> Result<(), Error> {
+    let config = &settings.config;
+    let active = config.active_profile();
+    if config.profiles.is_empty() {
+        println!("No profiles. Create one with: cf login --profile <name>");
+    }
+    for (name, profile) in &config.profiles {
+        let marker = if active.as_deref() == Some(name) {
+            "*"
+        } else {
+            " "
+        };
+        println!(
+            "{} {:<16} {}",
+            marker,
+            name,
+            profile.handle.as_deref().unwrap_or("(not logged in)")
+        );
+    }
+    if let Some(name) = active.filter(|n| !config.profiles.contains_key(n)) {
+        eprintln!("Active profile {} doesn't exist", name);
+    }
+    Ok(())
+}
+
+fn remove_profile(settings: &Settings, name: &str) -> Result<(), Error> {
+    let mut config = settings.config.clone();
+    if config.profiles.remove(name).is_none() {
+        return Err(format!("Unknown profile: {}", name).into());
+    }
+    if config.profile.as_deref() == Some(name) {
+        config.profile = None;
+    }
+    save_config(&config);
+    println!("Removed profile {}", name);
+    Ok(())
+}
+
+/// Submits over HTTP with the saved session, returning the submission ID
+/// if it could be found.
+fn submit_http(
+    settings: &Settings,
+    id: &ProblemId,
+    solution: &Path,
+    program_type_id: u32,
+) -> Result<Option<u64>, String> {
+    let Some(cookies) = settings.config.account().and_then(|a| a.cookies) else {
+        return Err(session::SessionError::NotLoggedIn.to_string());
     };
+    let source = fs::read_to_string(solution)
+        .map_err(|e| format!("Failed to read {}: {}", solution.display(), e))?;
+
+    let site = settings.site();
+    let session = session::Session::new(&site, settings.timeout(), Some(&cookies))?;
+    println!(
+        "Submitting {} as {} with {}...",
+        solution.display(),
+        id,
+        compiler_name(program_type_id)
+    );
+    let submission = session.submit(id, program_type_id, &source);
 
-    let (contest_id, problem_letter) = match parse_problem_name(name) {
-        Some(p) => p,
-        None => {
-            eprintln!("Invalid problem name: {}", name);
-            return;
+    // Keep refreshed session cookies
+    let mut config = settings.config.clone();
+    if let Some(cookies) = session.cookies() {
+        *config.account_mut().cookies = Some(cookies);
+        save_config(&config);
+    }
+
+    let submission = submission.map_err(|e| e.to_string())?;
+    match submission {
+        Some(submission) => println!("Submitted: {}", submission),
+        None => println!("Submitted"),
+    }
+    Ok(submission)
+}
+
+/// Give up waiting for a verdict after this long.
+const JUDGE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The ID of the account's newest submission, if it can be fetched.
+fn latest_submission_id(settings: &Settings) -> Option<u64> {
+    let handle = active_handle(settings).ok()?;
+    let submissions = api_client(settings).user_status(&handle, 1, 1).ok()?;
+    submissions.first().map(|s| s.id)
+}
+
+/// Polls the submission until it's judged, updating one status line, and
+/// fails unless it was accepted. Without an ID, the newest submission to the
+/// problem after `previous` (the newest one before submitting) is followed,
+/// so an earlier attempt's verdict isn't taken for this one.
+fn wait_for_verdict(
+    settings: &Settings,
+    id: &ProblemId,
+    submission: Option<u64>,
+    previous: Option<u64>,
+) -> Result<(), Error> {
+    let handle = active_handle(settings)?;
+    let client = api_client(settings);
+    let color = use_color(settings);
+    let live = io::stdout().is_terminal();
+    let name = id.to_string();
+    let start = Instant::now();
+    let mut last = String::new();
+
+    // The client spaces API calls, so polling in a loop is rate limited
+    loop {
+        let found = client
+            .user_status(&handle, 1, 10)?
+            .into_iter()
+            .find(|s| match submission {
+                Some(submission) => s.id == submission,
+                None => s.problem.short_name() == name && previous.is_none_or(|p| s.id > p),
+            });
+
+        if let Some(sub) = found.as_ref().filter(|s| s.is_judged()) {
+            let accepted = sub.verdict.as_deref() == Some("OK");
+            let text = format!(
+                "{}, {} ms, {} KB",
+                sub.verdict_text(),
+                sub.time_consumed_millis,
+                sub.memory_consumed_bytes / 1024
+            );
+            let text = paint(color, if accepted { GREEN } else { RED }, &text);
+            if live {
+                println!("\r\x1b[K{}", text);
+            } else {
+                println!("{}", text);
+            }
+            if !accepted {
+                return Err(Error::TestsFailed(String::new()));
+            }
+            return Ok(());
         }
+
+        let status = found.map_or("In queue".to_string(), |s| s.verdict_text()) + "...";
+        if status != last {
+            if live {
+                print!("\r\x1b[K{}", status);
+                let _ = io::stdout().flush();
+            } else {
+                println!("{}", status);
+            }
+            last = status;
+        }
+        if start.elapsed() > JUDGE_TIMEOUT {
+            if live {
+                println!();
+            }
+            return Err(format!("Still judging, check later with: cf watch {}", name).into());
+        }
+    }
+}
+
+/// Checks a solution before it's submitted: it must be in the expected
+/// language and pass the samples. Leftover debug output only warns.
+fn check_before_submit(
+    settings: &Settings,
+    name: &str,
+    lang: &str,
+    solution: &Path,
+) -> Result<(), Error> {
+    let ext = solution.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let expected = lang_ext(lang).unwrap_or("py");
+    if ext != expected {
+        return Err(format!(
+            "No {} solution for {}, found {} instead",
+            lang,
+            name,
+            solution.display()
+        )
+        .into());
+    }
+
+    let source = fs::read_to_string(solution).unwrap_or_default();
+    for (line, code) in lint::debug_lines(&source, ext) {
+        eprintln!(
+            "Warning: debug output at {}:{}: {}",
+            solution.display(),
+            line,
+            code
+        );
+    }
+
+    if !get_samples_dir(settings.project(), name).exists() {
+        eprintln!("Warning: no samples to check. Run: cf eg {}", name);
+        return Ok(());
+    }
+    let checker = resolve_checker(settings, None)?;
+    test_solution(settings, name, None, lang, checker, false, None).map_err(|err| match err {
+        Error::TestsFailed(_) => Error::TestsFailed("Samples failed".to_string()),
+        err => err,
+    })
+}
+
+/// Name of a compiler as shown on Codeforces, or its ID.
+fn compiler_name(program_type_id: u32) -> String {
+    compiler::describe(program_type_id)
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("programTypeId {}", program_type_id))
+}
+
+fn submit(
+    settings: &Settings,
+    id: &ProblemId,
+    lang: Option<String>,
+    browser: bool,
+    force: bool,
+) -> Result<(), Error> {
+    let name = id.to_string();
+    // `--lang` is a language, or one of its compilers like `pypy3`
+    let (lang, compiler) = match lang {
+        Some(lang) if lang_ext(&lang).is_none() => {
+            match compiler::COMPILERS.iter().find(|c| c.name == lang) {
+                Some(c) => (c.ext.to_string(), Some(lang)),
+                None => return Err(format!("Unknown language or compiler: {}", lang).into()),
+            }
+        }
+        lang => (resolve_lang(settings, lang), None),
     };
+    let solution = find_solution_file(settings, &name, &lang)
+        .ok_or_else(|| format!("No solution file found for {}", name))?;
+
+    if let Err(err) = check_before_submit(settings, &name, &lang, &solution) {
+        eprintln!("{}", err);
+        if !force {
+            let message = "Not submitting. Use --force to submit anyway".to_string();
+            return Err(err.with_message(message));
+        }
+        eprintln!("Submitting anyway (--force)");
+    }
 
     let ext = solution
         .extension()
         .and_then(|s| s.to_str())
-        .unwrap_or("py");
-    let url = format!(
-        "https://codeforces.com/contest/{}/submit/{}",
-        contest_id, problem_letter
-    );
+        .unwrap_or("py")
+        .to_string();
+    let solution = if ext == "rs" {
+        bundle_rust(settings.project(), &solution, &name)
+            .map_err(|err| format!("Failed to bundle {}: {}", solution.display(), err))?
+    } else {
+        solution
+    };
+    let compiler = compiler.or_else(|| settings.compiler(&ext));
+    let program_type_id = compiler::program_type_id(&ext, compiler.as_deref())?;
+    if !browser {
+        let previous = latest_submission_id(settings);
+        match submit_http(settings, id, &solution, program_type_id) {
+            Ok(submission) => return wait_for_verdict(settings, id, submission, previous),
+            Err(err) => {
+                eprintln!("{}", err);
+                eprintln!("Opening the submit page instead");
+            }
+        }
+    }
+
+    let url = id.submit_url(&settings.site());
 
     println!("Opening: {}", url);
     println!("File: {}", solution.display());
-    println!("Language: {}", ext);
+    println!("Language: {}", compiler_name(program_type_id));
+    if let Some(handle) = settings.handle() {
+        println!("Account: {}", handle);
+    }
 
-    // Open browser
-    #[cfg(target_os = "macos")]
-    let _ = Command::new("open").arg(&url).spawn();
-    #[cfg(target_os = "linux")]
-    let _ = Command::new("xdg-open").arg(&url).spawn();
-    #[cfg(target_os = "windows")]
-    let _ = Command::new("cmd").args(["/C", "start", &url]).spawn();
+    open_browser(settings, &url);
+    Ok(())
+}
+
+fn migrate(
+    settings: &Settings,
+    to: &str,
+    from: Option<&str>,
+    dry_run: bool,
+    force: bool,
+) -> Result<(), Error> {
+    let project = settings.project();
+    let to = Layout::new(to)?;
+    let from = match from {
+        Some(from) => Layout::new(from)?,
+        None => load_layout(settings),
+    };
+
+    let root = project.solutions_dir();
+    let mut moves = Vec::new();
+    // Files the current layout doesn't describe, which can't be moved
+    let mut unmatched = Vec::new();
+    for path in list_files(&root) {
+        let Some((name, ext)) = parse_solution_path(&root, &from, &path) else {
+            unmatched.push(path);
+            continue;
+        };
+        let rating = if to.needs_rating() {
+            fetch_problem_rating(settings, &name)
+        } else {
+            None
+        };
+        let target = root.join(to.render(&name, &ext, rating));
+        if target != path {
+            moves.push(migrate::Move {
+                from: path,
+                to: target,
+            });
+        }
+    }
+
+    // Samples are keyed by problem name, so only non-canonical names move
+    if let Ok(entries) = fs::read_dir(project.samples_dir()) {
+        for entry in entries.flatten() {
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            let name = canonical_name(&dir_name);
+            if entry.path().is_dir() && name != dir_name {
+                moves.push(migrate::Move {
+                    from: entry.path(),
+                    to: get_samples_dir(project, &name),
+                });
+            }
+        }
+    }
+
+    for m in &moves {
+        println!("{} -> {}", m.from.display(), m.to.display());
+    }
+
+    let conflicts = migrate::find_conflicts(&moves);
+    if !conflicts.is_empty() {
+        eprintln!("\nConflicts:");
+        for conflict in &conflicts {
+            eprintln!("  {}", conflict);
+        }
+        return Err(Error::Other("Nothing was moved.".to_string()));
+    }
+
+    if !unmatched.is_empty() {
+        eprintln!("\nNot matching solutions/{}:", from.pattern());
+        for path in &unmatched {
+            eprintln!("  {}", path.display());
+        }
+        if !force {
+            return Err(Error::Other(
+                "Nothing was moved. Use --force to leave these in place".to_string(),
+            ));
+        }
+    }
+
+    if dry_run {
+        println!("\n{} file(s) would be moved (dry run)", moves.len());
+        return Ok(());
+    }
+
+    migrate::apply(&moves, &[project.solutions_dir(), project.samples_dir()])?;
+
+    // Update the layout where it's configured
+    let result = match settings.get("layout") {
+        Some((_, Source::Project)) => config::set_project(project, "layout", Some(to.pattern())),
+        _ => {
+            let mut config = settings.config.clone();
+            config::set_global(&mut config, "layout", Some(to.pattern()))
+                .map(|_| save_config(&config))
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+    }
+    println!(
+        "\nMoved {} file(s). Layout is now solutions/{}",
+        moves.len(),
+        to.pattern()
+    );
+    Ok(())
+}
+
+fn list_snippets(project: &Project) -> Result<(), Error> {
+    let dir = project.snippets_dir();
+    let available = snippets::list(&dir);
+    if available.is_empty() {
+        println!("No snippets found in {}/", dir.display());
+        return Ok(());
+    }
+
+    println!("Snippets:");
+    for (name, exts) in &available {
+        println!("  {:<16} {}", name, exts.join(", "));
+    }
+    Ok(())
+}
+
+fn add_snippet(settings: &Settings, name: &str, snippet: &str, lang: &str) -> Result<(), Error> {
+    let project = settings.project();
+    let solution = find_solution_file(settings, name, lang)
+        .ok_or_else(|| format!("No solution file found for {}", name))?;
+
+    let ext = solution.extension().and_then(|s| s.to_str()).unwrap_or("");
+    let dir = project.snippets_dir();
+    let snippet_path = snippets::snippet_path(&dir, snippet, ext);
+    let code =
+        fs::read_to_string(&snippet_path).map_err(|_| match snippets::list(&dir).get(snippet) {
+            Some(exts) => format!(
+                "Snippet {} has no .{} version (available: {})",
+                snippet,
+                ext,
+                exts.join(", ")
+            ),
+            None => format!("Snippet not found: {}. Run: cf lib list", snippet),
+        })?;
+
+    let source = fs::read_to_string(&solution).unwrap_or_default();
+    let updated = snippets::insert(&source, &code)
+        .map_err(|err| format!("{}: {}", solution.display(), err))?;
+    write_file(&solution, updated)?;
+    println!("Added {} to {}", snippet, solution.display());
+    Ok(())
+}
+
+fn cache_stats() -> Result<(), Error> {
+    let cache = cache::Cache::open().ok_or_else(|| "No cache directory".to_string())?;
+    let stats = cache.stats();
+    println!("Cache: {}", cache.dir().display());
+    if stats.kinds.is_empty() {
+        println!("Empty");
+        return Ok(());
+    }
+    let mut total = (0, 0);
+    for (kind, (count, bytes)) in &stats.kinds {
+        println!("  {:<20} {:>5} {:>8} KB", kind, count, bytes / 1024);
+        total.0 += count;
+        total.1 += bytes;
+    }
+    println!("  --------");
+    println!(
+        "  {:<20} {:>5} {:>8} KB ({} expired)",
+        "Total",
+        total.0,
+        total.1 / 1024,
+        stats.expired
+    );
+    Ok(())
+}
+
+fn list_settings(settings: &Settings) -> Result<(), Error> {
+    let project = settings.project();
+    for key in config::KEYS {
+        match settings.get(key.name) {
+            Some((value, source)) => {
+                println!("{:<18} = {:<24} ({})", key.name, value, source)
+            }
+            None => println!("{:<18}   {:<24} (unset)", key.name, ""),
+        }
+    }
+    println!("\nGlobal: {}", config::config_path().display());
+    match &project.file {
+        Some(file) => println!("Project: {}", file.display()),
+        None => println!("Project: none (no {} found)", project::PROJECT_FILE),
+    }
+    Ok(())
+}
+
+fn get_setting(settings: &Settings, key: &str) -> Result<(), Error> {
+    let setting = config::find_key(key)?;
+    let (value, _) = settings
+        .get(key)
+        .ok_or_else(|| format!("{} is not set ({})", key, setting.description))?;
+    println!("{}", value);
+    Ok(())
+}
+
+fn set_setting(
+    settings: &Settings,
+    key: &str,
+    value: Option<&str>,
+    in_project: bool,
+) -> Result<(), Error> {
+    if in_project {
+        config::set_project(settings.project(), key, value)?;
+    } else {
+        let mut config = settings.config.clone();
+        config::set_global(&mut config, key, value)?;
+        save_config(&config);
+    }
+
+    // Point out when the new value is shadowed by a higher-precedence source
+    let target = if in_project {
+        Source::Project
+    } else {
+        Source::Global
+    };
+    if let Some((_, source)) = settings.reload().get(key) {
+        let shadowed = match source {
+            Source::Env(_) => true,
+            Source::Project => target == Source::Global,
+            _ => false,
+        };
+        if value.is_some() && shadowed {
+            eprintln!("Note: {} is overridden by the {} value", key, source);
+        }
+    }
+    Ok(())
 }
 
 fn main() {
     let cli = Cli::parse();
 
+    if let Err(err) = Project::discover()
+        .map_err(Error::from)
+        .and_then(|project| run(cli, &project))
+    {
+        let message = err.to_string();
+        if !message.is_empty() {
+            eprintln!("{}", message);
+        }
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn run(cli: Cli, project: &Project) -> Result<(), Error> {
+    let settings = Settings::load(project, cli.profile.clone()).with_offline(cli.offline);
     match cli.command {
         Commands::New {
             name,
             lang,
             single,
             fast,
-        } => generate(&name, &lang, single, fast),
-        Commands::List => list_templates(),
-        Commands::Eg { name, count } => create_samples(&name, count),
-        Commands::Test { name, num, lang } => test_solution(&name, num, &lang),
-        Commands::Login => login(),
-        Commands::Watch { name, ac } => watch(name, ac),
-        Commands::Submit { name } => submit(&name),
+            edit,
+        } => generate(
+            &settings,
+            &canonical_name(&name),
+            &resolve_lang(&settings, lang),
+            single,
+            fast,
+            edit,
+        ),
+        Commands::List => list_templates(&settings, cli.json),
+        Commands::Eg { name, count } => create_samples(&settings, &name.parse()?, count),
+        Commands::Test {
+            name,
+            num,
+            lang,
+            checker,
+            // Without a name clap requires `--all`
+            all: _,
+            report,
+        } => {
+            let checker = resolve_checker(&settings, checker)?;
+            match name {
+                Some(name) => test_solution(
+                    &settings,
+                    &canonical_name(&name),
+                    num,
+                    &resolve_lang(&settings, lang),
+                    checker,
+                    cli.json,
+                    report.as_deref(),
+                ),
+                None => test_all(
+                    &settings,
+                    lang.as_deref(),
+                    checker,
+                    cli.json,
+                    report.as_deref(),
+                ),
+            }
+        }
+        Commands::Login { api_key: false } => login(&settings),
+        Commands::Login { api_key: true } => login_api_key(&settings),
+        Commands::Friends { online } => friends(&settings, online),
+        Commands::Watch(mut args) => {
+            args.name = args.name.map(|n| canonical_name(&n));
+            watch(&settings, &args, cli.json)
+        }
+        Commands::Stats { handle } => stats(&settings, handle, cli.json),
+        Commands::Standings { contest, limit } => standings(&settings, contest, limit),
+        Commands::Profile { command } => match command {
+            ProfileCommands::List => list_profiles(&settings),
+            ProfileCommands::Use { name } => set_setting(&settings, "profile", Some(&name), false),
+            ProfileCommands::Remove { name } => remove_profile(&settings, &name),
+        },
+        Commands::Submit {
+            name,
+            browser,
+            lang,
+            force,
+        } => submit(&settings, &name.parse()?, lang, browser, force),
+        Commands::Migrate {
+            layout,
+            from,
+            dry_run,
+            force,
+        } => migrate(&settings, &layout, from.as_deref(), dry_run, force),
+        Commands::Lib { command } => match command {
+            LibCommands::List => list_snippets(project),
+            LibCommands::Add {
+                name,
+                snippet,
+                lang,
+            } => add_snippet(
+                &settings,
+                &canonical_name(&name),
+                &snippet,
+                &resolve_lang(&settings, lang),
+            ),
+        },
+        Commands::Cache { command } => match command {
+            CacheCommands::Stats => cache_stats(),
+            CacheCommands::Clear { expired } => {
+                let cache = cache::Cache::open().ok_or_else(|| "No cache directory".to_string())?;
+                println!("Removed {} cached responses", cache.clear(expired));
+                Ok(())
+            }
+        },
+        Commands::Config { command } => match command {
+            ConfigCommands::List => list_settings(&settings),
+            ConfigCommands::Get { key } => get_setting(&settings, &key),
+            ConfigCommands::Set {
+                key,
+                value,
+                project: in_project,
+            } => set_setting(&settings, &key, Some(&value), in_project),
+            ConfigCommands::Unset {
+                key,
+                project: in_project,
+            } => set_setting(&settings, &key, None, in_project),
+        },
     }
 }
 
@@ -710,32 +2321,12 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_is_cf_problem() {
-        assert!(is_cf_problem("1900A"));
-        assert!(is_cf_problem("123B"));
-        assert!(is_cf_problem("1A"));
-        assert!(!is_cf_problem("2000F1")); // ends with digit, not letter
-    }
-
-    #[test]
-    fn test_is_cf_problem_invalid() {
-        assert!(!is_cf_problem("abc"));
-        assert!(!is_cf_problem("A"));
-        assert!(!is_cf_problem("leetcode"));
-        assert!(!is_cf_problem(""));
-    }
-
-    #[test]
-    fn test_extract_problem_letter() {
-        assert_eq!(extract_problem_letter("1900A"), Some('A'));
-        assert_eq!(extract_problem_letter("123B"), Some('B'));
-        assert_eq!(extract_problem_letter("1a"), Some('a'));
-    }
-
-    #[test]
-    fn test_extract_problem_letter_others() {
-        assert_eq!(extract_problem_letter("abc"), None);
-        assert_eq!(extract_problem_letter("leetcode"), None);
-        assert_eq!(extract_problem_letter(""), None);
+    fn test_canonical_name() {
+        assert_eq!(canonical_name("1900a"), "1900A");
+        assert_eq!(
+            canonical_name("https://codeforces.com/contest/2000/problem/F1"),
+            "2000F1"
+        );
+        assert_eq!(canonical_name("leetcode"), "leetcode");
     }
 }
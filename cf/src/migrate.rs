@@ -0,0 +1,215 @@
+//! Moving solutions and samples from one layout to another.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A single file or directory to move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Move {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Describes why moves can't all be done: two sources with the same target,
+/// a target that already exists and isn't moved away itself, or moves onto
+/// each other's sources in a cycle.
+pub fn find_conflicts(moves: &[Move]) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    let mut targets: HashMap<&Path, &Path> = HashMap::new();
+    for m in moves {
+        if let Some(other) = targets.insert(&m.to, &m.from) {
+            conflicts.push(format!(
+                "{} and {} both map to {}",
+                other.display(),
+                m.from.display(),
+                m.to.display()
+            ));
+        }
+    }
+
+    for m in moves {
+        let vacated = moves.iter().any(|other| other.from == m.to);
+        if m.to.exists() && !vacated {
+            conflicts.push(format!(
+                "{} -> {}: target already exists",
+                m.from.display(),
+                m.to.display()
+            ));
+        }
+    }
+
+    if let Err(cycle) = order(moves) {
+        for m in cycle {
+            conflicts.push(format!(
+                "{} -> {}: moves form a cycle",
+                m.from.display(),
+                m.to.display()
+            ));
+        }
+    }
+    conflicts
+}
+
+/// Orders moves so every target is moved away before anything moves onto
+/// it. Fails with the moves left waiting on each other in a cycle.
+fn order(moves: &[Move]) -> Result<Vec<&Move>, Vec<&Move>> {
+    let mut pending: Vec<&Move> = moves.iter().collect();
+    let mut ordered = Vec::with_capacity(moves.len());
+    while !pending.is_empty() {
+        let (ready, waiting): (Vec<&Move>, Vec<&Move>) = pending
+            .iter()
+            .copied()
+            .partition(|m| !pending.iter().any(|other| other.from == m.to));
+        if ready.is_empty() {
+            return Err(waiting);
+        }
+        ordered.extend(ready);
+        pending = waiting;
+    }
+    Ok(ordered)
+}
+
+fn in_git_repo() -> bool {
+    Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Performs the moves, each target's own move first, using `git mv` inside a
+/// git repository so history is kept. Files git doesn't track are renamed
+/// directly. Directories left empty are removed, up to the `roots` they're
+/// in (`solutions` and `samples`).
+pub fn apply(moves: &[Move], roots: &[PathBuf]) -> Result<(), String> {
+    let moves = order(moves).map_err(|_| "Moves form a cycle, nothing was moved".to_string())?;
+    let roots: Vec<PathBuf> = roots.iter().filter_map(|r| r.canonicalize().ok()).collect();
+    let git = in_git_repo();
+    for m in moves {
+        if let Some(parent) = m.to.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+
+        let moved_by_git = git
+            && Command::new("git")
+                .arg("mv")
+                .arg(&m.from)
+                .arg(&m.to)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false);
+        if !moved_by_git {
+            fs::rename(&m.from, &m.to).map_err(|e| {
+                format!(
+                    "Failed to move {} to {}: {}",
+                    m.from.display(),
+                    m.to.display(),
+                    e
+                )
+            })?;
+        }
+
+        if let Some(parent) = m.from.parent() {
+            remove_empty_dirs(parent, &roots);
+        }
+    }
+    Ok(())
+}
+
+/// Removes `dir` and its parents while they're empty, stopping at the
+/// canonical `roots`, which are kept. Directories outside them are never
+/// removed.
+fn remove_empty_dirs(dir: &Path, roots: &[PathBuf]) {
+    let Ok(mut dir) = dir.canonicalize() else {
+        return;
+    };
+    while roots
+        .iter()
+        .any(|root| dir.starts_with(root) && dir != *root)
+    {
+        if fs::remove_dir(&dir).is_err() {
+            break;
+        }
+        match dir.parent() {
+            Some(parent) => dir = parent.to_path_buf(),
+            None => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(from: &str, to: &str) -> Move {
+        Move {
+            from: PathBuf::from(from),
+            to: PathBuf::from(to),
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let moves = [
+            mv("no-such-dir/A-set/1A.py", "no-such-dir/1/A.py"),
+            mv("no-such-dir/Others/1A.py", "no-such-dir/1/A.py"),
+        ];
+        assert_eq!(find_conflicts(&moves).len(), 1);
+
+        let moves = [
+            mv("no-such-dir/A-set/1A.py", "no-such-dir/1/A.py"),
+            mv("no-such-dir/B-set/1B.py", "no-such-dir/1/B.py"),
+        ];
+        assert!(find_conflicts(&moves).is_empty());
+    }
+
+    #[test]
+    fn test_order() {
+        let moves = [
+            mv("no-such-dir/A.py", "no-such-dir/B.py"),
+            mv("no-such-dir/B.py", "no-such-dir/C.py"),
+        ];
+        let ordered = order(&moves).unwrap();
+        assert_eq!(ordered, [&moves[1], &moves[0]]);
+
+        let moves = [
+            mv("no-such-dir/A.py", "no-such-dir/B.py"),
+            mv("no-such-dir/B.py", "no-such-dir/A.py"),
+            mv("no-such-dir/C.py", "no-such-dir/D.py"),
+        ];
+        assert_eq!(order(&moves).unwrap_err(), [&moves[0], &moves[1]]);
+        assert_eq!(find_conflicts(&moves).len(), 2);
+    }
+
+    #[test]
+    fn test_remove_empty_dirs_keeps_roots() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("solutions");
+        let dir = root.join("contests/1900");
+        fs::create_dir_all(&dir).unwrap();
+
+        // Relative paths climb through the root, which must stay
+        let relative = dir.join("../../../solutions/contests/1900");
+        remove_empty_dirs(&relative, &[root.canonicalize().unwrap()]);
+        assert!(!root.join("contests").exists());
+        assert!(root.exists());
+
+        let outside = tmp.path().join("other/dir");
+        fs::create_dir_all(&outside).unwrap();
+        remove_empty_dirs(&outside, &[root.canonicalize().unwrap()]);
+        assert!(outside.exists());
+    }
+
+    #[test]
+    fn test_existing_target_conflicts() {
+        let existing = std::env::current_exe().unwrap();
+        let moves = [Move {
+            from: PathBuf::from("no-such-dir/1A.py"),
+            to: existing,
+        }];
+        assert_eq!(find_conflicts(&moves).len(), 1);
+    }
+}
@@ -0,0 +1,282 @@
+//! Codeforces problem identifiers.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Where problems live unless `base_url` points elsewhere.
+pub const DEFAULT_SITE: &str = "https://codeforces.com";
+
+/// Gym contests use IDs from 100000 up.
+pub const GYM_CONTEST_MIN: u64 = 100_000;
+
+/// The acm.sgu problems all live in this pseudo-contest.
+const ACMSGURU_CONTEST: u64 = 99_999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProblemKind {
+    Contest,
+    Gym,
+    Acmsguru,
+}
+
+impl ProblemKind {
+    fn for_contest(contest: u64) -> ProblemKind {
+        if contest >= GYM_CONTEST_MIN {
+            ProblemKind::Gym
+        } else {
+            ProblemKind::Contest
+        }
+    }
+}
+
+/// A Codeforces problem such as `1900A`, `2000F1`, `102001K` (gym) or
+/// `sgu100` (acm.sgu).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProblemId {
+    pub kind: ProblemKind,
+    pub contest: u64,
+    /// Problem index within the contest, uppercased (`A`, `F1`, ...). For
+    /// acm.sgu this is the problem number.
+    pub index: String,
+}
+
+impl ProblemId {
+    /// The problem letter, used for the `X-set` directories (`F` for `F1`).
+    pub fn letter(&self) -> char {
+        self.index.chars().next().unwrap_or('A')
+    }
+
+    pub fn problem_url(&self, site: &str) -> String {
+        match self.kind {
+            ProblemKind::Contest => {
+                format!("{}/contest/{}/problem/{}", site, self.contest, self.index)
+            }
+            ProblemKind::Gym => format!("{}/gym/{}/problem/{}", site, self.contest, self.index),
+            ProblemKind::Acmsguru => format!(
+                "{}/problemsets/acmsguru/problem/{}/{}",
+                site, self.contest, self.index
+            ),
+        }
+    }
+
+    pub fn submit_url(&self, site: &str) -> String {
+        match self.kind {
+            ProblemKind::Contest => {
+                format!("{}/contest/{}/submit/{}", site, self.contest, self.index)
+            }
+            ProblemKind::Gym => format!("{}/gym/{}/submit/{}", site, self.contest, self.index),
+            ProblemKind::Acmsguru => format!(
+                "{}/problemsets/acmsguru/submit/{}/{}",
+                site, self.contest, self.index
+            ),
+        }
+    }
+
+    /// Builds an ID from a contest ID and problem index as found in file
+    /// paths or API responses.
+    pub fn from_parts(contest: &str, index: &str) -> Result<ProblemId, String> {
+        if contest == ACMSGURU_CONTEST.to_string() {
+            format!("problemsets/acmsguru/problem/{}/{}", contest, index).parse()
+        } else {
+            format!("contest/{}/problem/{}", contest, index).parse()
+        }
+    }
+}
+
+impl fmt::Display for ProblemId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ProblemKind::Acmsguru => write!(f, "sgu{}", self.index),
+            _ => write!(f, "{}{}", self.contest, self.index),
+        }
+    }
+}
+
+impl FromStr for ProblemId {
+    type Err = String;
+
+    /// Accepts `1900A`, `2000f1`, `sgu100`, `contest/1900/problem/A`,
+    /// `gym/102001/problem/K`, `problemset/problem/1900/A`,
+    /// `problemsets/acmsguru/problem/99999/100` and full URLs.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid problem name: {}. Use format like 1900A", s);
+
+        let path = strip_host(s.trim());
+        let path = path.split(['?', '#']).next().unwrap_or("");
+        let segments: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+
+        let (kind, contest, index) = match segments.as_slice() {
+            [compact]
+                if compact
+                    .get(..3)
+                    .is_some_and(|p| p.eq_ignore_ascii_case("sgu")) =>
+            {
+                (ProblemKind::Acmsguru, "99999", &compact[3..])
+            }
+            ["problemsets", "acmsguru", "problem", contest, index] => {
+                (ProblemKind::Acmsguru, *contest, *index)
+            }
+            [compact] => {
+                let (contest, index) = split_compact(compact).ok_or_else(invalid)?;
+                let kind = ProblemKind::for_contest(contest.parse().map_err(|_| invalid())?);
+                (kind, contest, index)
+            }
+            ["gym", contest, "problem", index] => (ProblemKind::Gym, *contest, *index),
+            ["contest", contest, "problem", index] | ["problemset", "problem", contest, index] => {
+                let kind = ProblemKind::for_contest(contest.parse().map_err(|_| invalid())?);
+                (kind, *contest, *index)
+            }
+            _ => return Err(invalid()),
+        };
+
+        let contest = contest.parse::<u64>().map_err(|_| invalid())?;
+        let valid = match kind {
+            ProblemKind::Acmsguru => {
+                contest == ACMSGURU_CONTEST
+                    && !index.is_empty()
+                    && index.chars().all(|c| c.is_ascii_digit())
+            }
+            _ => is_valid_index(index),
+        };
+        if !valid {
+            return Err(invalid());
+        }
+        Ok(ProblemId {
+            kind,
+            contest,
+            index: index.to_ascii_uppercase(),
+        })
+    }
+}
+
+/// Drops `https://codeforces.com/` (or any scheme and host) from a URL.
+fn strip_host(s: &str) -> &str {
+    let rest = match s.find("://") {
+        Some(i) => &s[i + 3..],
+        None if s.contains("codeforces.") => s,
+        None => return s,
+    };
+    rest.find('/').map_or("", |i| &rest[i..])
+}
+
+/// Splits `1900A` into `("1900", "A")`.
+fn split_compact(s: &str) -> Option<(&str, &str)> {
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    if split == 0 {
+        return None;
+    }
+    Some((&s[..split], &s[split..]))
+}
+
+/// A letter optionally followed by a digit: `A`, `F1`.
+fn is_valid_index(index: &str) -> bool {
+    let mut chars = index.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() => {}
+        _ => return false,
+    }
+    let rest = chars.as_str();
+    rest.len() <= 1 && rest.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Option<String> {
+        s.parse::<ProblemId>().ok().map(|id| id.to_string())
+    }
+
+    #[test]
+    fn test_parse_compact() {
+        assert_eq!(parse("1900A").as_deref(), Some("1900A"));
+        assert_eq!(parse("123B").as_deref(), Some("123B"));
+        assert_eq!(parse("1A").as_deref(), Some("1A"));
+        assert_eq!(parse("1900a").as_deref(), Some("1900A"));
+        assert_eq!(parse("2000F1").as_deref(), Some("2000F1"));
+        assert_eq!(parse("1950e2").as_deref(), Some("1950E2"));
+        assert_eq!(parse("102001K").as_deref(), Some("102001K"));
+    }
+
+    #[test]
+    fn test_parse_paths_and_urls() {
+        assert_eq!(parse("contest/1900/problem/A").as_deref(), Some("1900A"));
+        assert_eq!(parse("gym/102001/problem/K").as_deref(), Some("102001K"));
+        assert_eq!(
+            parse("problemset/problem/2000/F1").as_deref(),
+            Some("2000F1")
+        );
+        assert_eq!(
+            parse("https://codeforces.com/contest/1900/problem/A").as_deref(),
+            Some("1900A")
+        );
+        assert_eq!(
+            parse("https://codeforces.com/problemset/problem/1950/E2?locale=en").as_deref(),
+            Some("1950E2")
+        );
+        assert_eq!(
+            parse("codeforces.com/gym/102001/problem/K/").as_deref(),
+            Some("102001K")
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse("abc"), None);
+        assert_eq!(parse("A"), None);
+        assert_eq!(parse("leetcode"), None);
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("1900"), None);
+        assert_eq!(parse("1900ABC"), None);
+        assert_eq!(parse("2000F12"), None);
+        assert_eq!(parse("contest/abc/problem/A"), None);
+    }
+
+    #[test]
+    fn test_parse_kinds() {
+        let kind = |s: &str| s.parse::<ProblemId>().unwrap().kind;
+        assert_eq!(kind("1900A"), ProblemKind::Contest);
+        assert_eq!(kind("102001K"), ProblemKind::Gym);
+        assert_eq!(kind("gym/102001/problem/K"), ProblemKind::Gym);
+        assert_eq!(kind("sgu100"), ProblemKind::Acmsguru);
+        assert_eq!(
+            parse("https://codeforces.com/problemsets/acmsguru/problem/99999/100").as_deref(),
+            Some("sgu100")
+        );
+        assert_eq!(parse("SGU105").as_deref(), Some("sgu105"));
+        assert_eq!(parse("sguA"), None);
+    }
+
+    #[test]
+    fn test_from_parts() {
+        let id = |c: &str, i: &str| ProblemId::from_parts(c, i).ok().map(|id| id.to_string());
+        assert_eq!(id("1900", "a").as_deref(), Some("1900A"));
+        assert_eq!(id("102001", "K").as_deref(), Some("102001K"));
+        assert_eq!(id("99999", "100").as_deref(), Some("sgu100"));
+        assert_eq!(id("x", "A"), None);
+    }
+
+    #[test]
+    fn test_urls() {
+        let id: ProblemId = "102001K".parse().unwrap();
+        assert_eq!(
+            id.problem_url(DEFAULT_SITE),
+            "https://codeforces.com/gym/102001/problem/K"
+        );
+        assert_eq!(
+            id.submit_url(DEFAULT_SITE),
+            "https://codeforces.com/gym/102001/submit/K"
+        );
+        let id: ProblemId = "sgu100".parse().unwrap();
+        assert_eq!(
+            id.problem_url(DEFAULT_SITE),
+            "https://codeforces.com/problemsets/acmsguru/problem/99999/100"
+        );
+    }
+
+    #[test]
+    fn test_letter() {
+        assert_eq!("1900A".parse::<ProblemId>().unwrap().letter(), 'A');
+        assert_eq!("2000f1".parse::<ProblemId>().unwrap().letter(), 'F');
+    }
+}
@@ -0,0 +1,201 @@
+//! Project configuration from a `cf.toml` found by walking up from the
+//! current directory, so `cf` works from any subdirectory of the repository.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PROJECT_FILE: &str = "cf.toml";
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Directory holding `solutions/` and `samples/`, relative to `cf.toml`
+    pub root: Option<PathBuf>,
+    /// Default language for `new`, `test` and `lib add`
+    pub lang: Option<String>,
+    /// Template directory, relative to `cf.toml`
+    pub templates: Option<PathBuf>,
+    /// Snippet directory, relative to the root
+    pub snippets: Option<PathBuf>,
+    /// Rust library crate, relative to the root
+    pub library: Option<PathBuf>,
+    /// Solutions layout pattern, see `layout::Layout`
+    pub layout: Option<String>,
+    pub editor: Option<String>,
+    pub browser: Option<String>,
+    pub time_limit_factor: Option<f64>,
+    pub timeout: Option<f64>,
+    pub base_url: Option<String>,
+    pub color: Option<String>,
+    pub test: TestConfig,
+    /// Compiler for `cf submit` by language extension, see
+    /// `compiler::COMPILERS`
+    pub compiler: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct TestConfig {
+    /// Default checker for `cf test`, see `checker::Checker`
+    pub checker: Option<String>,
+}
+
+pub struct Project {
+    /// Location of `cf.toml`, if there is one.
+    pub file: Option<PathBuf>,
+    /// Directory containing `cf.toml` (or the current directory without
+    /// one), relative to the current directory.
+    dir: PathBuf,
+    pub config: ProjectConfig,
+}
+
+impl Project {
+    /// Finds the nearest `cf.toml` in the current directory or its parents.
+    /// Without one, everything is relative to the current directory.
+    pub fn discover() -> Result<Project, String> {
+        let cwd = std::env::current_dir().map_err(|e| format!("No current directory: {}", e))?;
+        let mut dir = PathBuf::new();
+        for ancestor in cwd.ancestors() {
+            let file = ancestor.join(PROJECT_FILE);
+            if file.is_file() {
+                return Project::load(dir.join(PROJECT_FILE), dir);
+            }
+            dir.push("..");
+        }
+        Ok(Project {
+            file: None,
+            dir: PathBuf::new(),
+            config: ProjectConfig::default(),
+        })
+    }
+
+    fn load(file: PathBuf, dir: PathBuf) -> Result<Project, String> {
+        let text = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let config = toml::from_str(&text).map_err(|e| format!("{}: {}", file.display(), e))?;
+        Ok(Project {
+            file: Some(file),
+            dir,
+            config,
+        })
+    }
+
+    pub fn root(&self) -> PathBuf {
+        match &self.config.root {
+            Some(root) => self.dir.join(root),
+            None => self.dir.clone(),
+        }
+    }
+
+    pub fn solutions_dir(&self) -> PathBuf {
+        self.root().join("solutions")
+    }
+
+    pub fn samples_dir(&self) -> PathBuf {
+        self.root().join("samples")
+    }
+
+    pub fn snippets_dir(&self) -> PathBuf {
+        self.root().join(
+            self.config
+                .snippets
+                .as_deref()
+                .unwrap_or(Path::new("snippets")),
+        )
+    }
+
+    pub fn library_dir(&self) -> PathBuf {
+        self.root().join(
+            self.config
+                .library
+                .as_deref()
+                .unwrap_or(Path::new("library")),
+        )
+    }
+
+    /// Directory for generated files such as bundled Rust solutions.
+    pub fn target_dir(&self) -> PathBuf {
+        self.root().join("target")
+    }
+
+    pub fn templates_dir(&self) -> Option<PathBuf> {
+        self.config.templates.as_ref().map(|t| self.dir.join(t))
+    }
+
+    /// Sets (or with `None`, removes) a key in `cf.toml`, either at the top
+    /// level or in `[table]`, keeping the file's formatting.
+    pub fn set(
+        &self,
+        table: Option<&str>,
+        key: &str,
+        value: Option<toml_edit::Item>,
+    ) -> Result<(), String> {
+        let file = self
+            .file
+            .as_ref()
+            .ok_or_else(|| format!("No {} found", PROJECT_FILE))?;
+        let text = fs::read_to_string(file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let mut doc = text
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| format!("{}: {}", file.display(), e))?;
+        let target = match table {
+            Some(table) => &mut doc[table],
+            None => doc.as_item_mut(),
+        };
+        match value {
+            Some(value) => target[key] = value,
+            None => {
+                if let Some(t) = target.as_table_like_mut() {
+                    t.remove(key);
+                }
+            }
+        }
+        fs::write(file, doc.to_string())
+            .map_err(|e| format!("Failed to write {}: {}", file.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: ProjectConfig = toml::from_str(
+            "lang = \"cpp\"\nlayout = \"{set}/{name}.{ext}\"\n\n[test]\nchecker = \"tokens\"\n\n\
+             [compiler]\ncpp = \"clang++20\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.lang.as_deref(), Some("cpp"));
+        assert_eq!(config.test.checker.as_deref(), Some("tokens"));
+        assert_eq!(config.compiler["cpp"], "clang++20");
+        assert!(config.root.is_none());
+
+        assert!(toml::from_str::<ProjectConfig>("langauge = \"cpp\"\n").is_err());
+    }
+
+    #[test]
+    fn test_paths() {
+        let project = Project {
+            file: Some(PathBuf::from("../../cf.toml")),
+            dir: PathBuf::from("../.."),
+            config: toml::from_str("templates = \"cf/templates\"\nsnippets = \"lib\"\n").unwrap(),
+        };
+        assert_eq!(project.solutions_dir(), Path::new("../../solutions"));
+        assert_eq!(project.snippets_dir(), Path::new("../../lib"));
+        assert_eq!(
+            project.templates_dir().unwrap(),
+            Path::new("../../cf/templates")
+        );
+
+        let project = Project {
+            file: None,
+            dir: PathBuf::new(),
+            config: ProjectConfig::default(),
+        };
+        assert_eq!(project.samples_dir(), Path::new("samples"));
+    }
+}
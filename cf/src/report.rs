@@ -0,0 +1,187 @@
+//! Results of testing solutions on their samples, and JUnit XML reports of
+//! them for CI.
+
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Outcome of one sample.
+#[derive(Serialize)]
+pub struct TestCase {
+    pub test: usize,
+    /// `passed`, `failed` or `time_limit_exceeded`
+    pub verdict: &'static str,
+    pub time_ms: u128,
+    pub expected: String,
+    pub output: String,
+}
+
+/// Outcome of testing one solution.
+#[derive(Serialize)]
+pub struct TestRun {
+    pub name: String,
+    pub solution: PathBuf,
+    /// Solution file extension
+    pub lang: String,
+    pub tests: Vec<TestCase>,
+    /// Why no sample could run, e.g. a compilation error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub compilation_failed: bool,
+}
+
+impl TestRun {
+    pub fn passed(&self) -> usize {
+        self.tests.iter().filter(|c| c.verdict == "passed").count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.tests.len() - self.passed()
+    }
+
+    /// Whether it ran and passed every sample.
+    pub fn ok(&self) -> bool {
+        self.error.is_none() && !self.tests.is_empty() && self.failed() == 0
+    }
+}
+
+/// Line by line differences, as `-expected` and `+output` lines after the
+/// line number.
+pub fn diff(expected: &str, output: &str) -> String {
+    let expected: Vec<&str> = expected.trim_end().lines().collect();
+    let output: Vec<&str> = output.trim_end().lines().collect();
+    let mut diff = String::new();
+    for i in 0..expected.len().max(output.len()) {
+        let (e, o) = (expected.get(i), output.get(i));
+        if e.map(|l| l.trim_end()) == o.map(|l| l.trim_end()) {
+            continue;
+        }
+        diff.push_str(&format!("line {}:\n", i + 1));
+        if let Some(e) = e {
+            diff.push_str(&format!("-{}\n", e));
+        }
+        if let Some(o) = o {
+            diff.push_str(&format!("+{}\n", o));
+        }
+    }
+    diff
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn seconds(ms: u128) -> String {
+    format!("{:.3}", ms as f64 / 1000.0)
+}
+
+/// A JUnit XML report with a test suite per solution and a test case per
+/// sample. A solution that couldn't run gets one erroring test case.
+pub fn junit(runs: &[TestRun]) -> String {
+    let total = |f: fn(&TestRun) -> usize| runs.iter().map(f).sum::<usize>();
+    let time = |run: &TestRun| run.tests.iter().map(|c| c.time_ms).sum::<u128>();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"cf\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">\n",
+        total(|r| r.tests.len().max(r.error.is_some() as usize)),
+        total(TestRun::failed),
+        total(|r| r.error.is_some() as usize),
+        seconds(runs.iter().map(time).sum())
+    ));
+
+    for run in runs {
+        let suite = format!("{}.{}", run.name, run.lang);
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">\n",
+            escape(&suite),
+            run.tests.len().max(run.error.is_some() as usize),
+            run.failed(),
+            run.error.is_some() as usize,
+            seconds(time(run))
+        ));
+        if let Some(error) = &run.error {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"run\">\n      <error message=\"{}\">{}</error>\n    </testcase>\n",
+                escape(&suite),
+                escape(error.lines().next().unwrap_or("")),
+                escape(error)
+            ));
+        }
+        for case in &run.tests {
+            xml.push_str(&format!(
+                "    <testcase classname=\"{}\" name=\"sample {}\" time=\"{}\"",
+                escape(&suite),
+                case.test,
+                seconds(case.time_ms)
+            ));
+            match case.verdict {
+                "passed" => xml.push_str("/>\n"),
+                "time_limit_exceeded" => xml.push_str(
+                    ">\n      <failure message=\"Time limit exceeded\"/>\n    </testcase>\n",
+                ),
+                _ => xml.push_str(&format!(
+                    ">\n      <failure message=\"Wrong answer\">{}</failure>\n    </testcase>\n",
+                    escape(&diff(&case.expected, &case.output))
+                )),
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_junit() {
+        let case = |test, verdict: &'static str, output: &str| TestCase {
+            test,
+            verdict,
+            time_ms: 12,
+            expected: "1\n2 3\n".to_string(),
+            output: output.to_string(),
+        };
+        let runs = [
+            TestRun {
+                name: "1900A".to_string(),
+                solution: PathBuf::from("solutions/A-set/1900A.py"),
+                lang: "py".to_string(),
+                tests: vec![case(1, "passed", "1\n2 3\n"), case(2, "failed", "1\n2 4\n")],
+                error: None,
+                compilation_failed: false,
+            },
+            TestRun {
+                name: "1900B".to_string(),
+                solution: PathBuf::from("solutions/B-set/1900B.cpp"),
+                lang: "cpp".to_string(),
+                tests: Vec::new(),
+                error: Some("error: expected ';' & <stuff>".to_string()),
+                compilation_failed: true,
+            },
+        ];
+        assert!(!runs[0].ok());
+        assert_eq!(diff("1\n2 3\n", "1\n2 4\n"), "line 2:\n-2 3\n+2 4\n");
+
+        let xml = junit(&runs);
+        assert!(xml.contains("<testsuites name=\"cf\" tests=\"3\" failures=\"1\" errors=\"1\""));
+        assert!(xml.contains("<testcase classname=\"1900A.py\" name=\"sample 1\" time=\"0.012\"/>"));
+        assert!(xml.contains("<failure message=\"Wrong answer\">line 2:\n-2 3\n+2 4\n</failure>"));
+        assert!(xml.contains("expected &apos;;&apos; &amp; &lt;stuff&gt;"));
+    }
+}
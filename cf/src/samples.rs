@@ -0,0 +1,86 @@
+//! Sample tests scraped from a problem statement page.
+
+use scraper::{ElementRef, Html, Node, Selector};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub input: String,
+    pub output: String,
+}
+
+/// Finds the samples in a Codeforces problem page, in order.
+pub fn parse_samples(html: &str) -> Vec<Sample> {
+    let doc = Html::parse_document(html);
+    let inputs = Selector::parse("div.sample-test div.input pre").expect("valid selector");
+    let outputs = Selector::parse("div.sample-test div.output pre").expect("valid selector");
+    doc.select(&inputs)
+        .zip(doc.select(&outputs))
+        .map(|(input, output)| Sample {
+            input: pre_text(input),
+            output: pre_text(output),
+        })
+        .collect()
+}
+
+/// Text of a `<pre>`, whose lines are separated by `<br>` in older problems
+/// and wrapped in `<div class="test-example-line">` in newer ones.
+fn pre_text(pre: ElementRef) -> String {
+    let mut text = String::new();
+    collect_text(pre, &mut text);
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let text = lines.join("\n");
+    format!("{}\n", text.trim_matches('\n'))
+}
+
+fn collect_text(element: ElementRef, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(e) if e.name() == "br" => out.push('\n'),
+            Node::Element(e) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    collect_text(child, out);
+                }
+                if e.name() == "div" && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_divs() {
+        let html = r#"<div class="sample-tests"><div class="sample-test">
+            <div class="input"><div class="title">Input</div><pre>
+<div class="test-example-line test-example-line-even test-example-line-0">2</div><div class="test-example-line test-example-line-odd test-example-line-1">1 2</div></pre></div>
+            <div class="output"><div class="title">Output</div><pre>
+3
+</pre></div></div></div>"#;
+        assert_eq!(
+            parse_samples(html),
+            vec![Sample {
+                input: "2\n1 2\n".to_string(),
+                output: "3\n".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_br_lines() {
+        let html = r#"<div class="sample-test">
+            <div class="input"><pre>1<br />5 &lt; 6<br /></pre></div>
+            <div class="output"><pre>YES<br /></pre></div>
+            <div class="input"><pre>0</pre></div>
+            <div class="output"><pre>NO</pre></div></div>"#;
+        let samples = parse_samples(html);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].input, "1\n5 < 6\n");
+        assert_eq!(samples[1].output, "NO\n");
+    }
+}
@@ -0,0 +1,263 @@
+//! Logged-in web session for what the API can't do, like submitting.
+//! Cookies are kept in the config between runs.
+
+use crate::problem::{ProblemId, ProblemKind};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::Url;
+use scraper::{Html, Selector};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum SessionError {
+    Network(String),
+    /// The page didn't look like we expected, e.g. no CSRF token
+    Parse(String),
+    /// No valid session cookies, or a wrong password
+    NotLoggedIn,
+    /// Codeforces rejected the form, e.g. the same code was submitted before
+    Rejected(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Network(e) => write!(f, "Network error: {}", e),
+            SessionError::Parse(e) => write!(f, "Unexpected page: {}", e),
+            SessionError::NotLoggedIn => {
+                write!(f, "Not logged in on the website. Run: cf login")
+            }
+            SessionError::Rejected(reason) => write!(f, "Rejected: {}", reason),
+        }
+    }
+}
+
+pub struct Session {
+    http: reqwest::blocking::Client,
+    jar: Arc<Jar>,
+    site: Url,
+    /// Browser fingerprint fields Codeforces expects with every form
+    ftaa: String,
+    bfaa: String,
+}
+
+impl Session {
+    /// Starts a session with cookies saved by `cookies()`, if any.
+    pub fn new(site: &str, timeout: Duration, cookies: Option<&str>) -> Result<Session, String> {
+        let site = Url::parse(site).map_err(|e| format!("Invalid base URL {}: {}", site, e))?;
+        let jar = Arc::new(Jar::default());
+        for cookie in cookies.unwrap_or("").split(';') {
+            if !cookie.trim().is_empty() {
+                jar.add_cookie_str(cookie.trim(), &site);
+            }
+        }
+        let http = reqwest::blocking::Client::builder()
+            .cookie_provider(Arc::clone(&jar))
+            .timeout(timeout)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Session {
+            http,
+            jar,
+            site,
+            ftaa: random_string("abcdefghijklmnopqrstuvwxyz0123456789", 18),
+            bfaa: random_string("0123456789abcdef", 32),
+        })
+    }
+
+    /// Session cookies to save, as `name=value; name=value`.
+    pub fn cookies(&self) -> Option<String> {
+        let header = self.jar.cookies(&self.site)?;
+        header.to_str().ok().map(str::to_string)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.site.as_str().trim_end_matches('/'), path)
+    }
+
+    fn get(&self, url: &str) -> Result<String, SessionError> {
+        self.http
+            .get(url)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .map_err(|e| SessionError::Network(e.to_string()))
+    }
+
+    /// Posts a form, returning the final URL after redirects and the page.
+    fn post(&self, url: &str, form: &[(&str, &str)]) -> Result<(Url, String), SessionError> {
+        let response = self
+            .http
+            .post(url)
+            .form(form)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| SessionError::Network(e.to_string()))?;
+        let url = response.url().clone();
+        let text = response
+            .text()
+            .map_err(|e| SessionError::Network(e.to_string()))?;
+        Ok((url, text))
+    }
+
+    /// Logs in with a password, returning the handle as Codeforces spells it.
+    pub fn login(&self, handle: &str, password: &str) -> Result<String, SessionError> {
+        let url = self.url("/enter");
+        let csrf = csrf_token(&self.get(&url)?)?;
+        let (_, page) = self.post(
+            &url,
+            &[
+                ("csrf_token", &csrf),
+                ("action", "enter"),
+                ("ftaa", &self.ftaa),
+                ("bfaa", &self.bfaa),
+                ("handleOrEmail", handle),
+                ("password", password),
+                ("_tta", "176"),
+                ("remember", "on"),
+            ],
+        )?;
+        logged_in_handle(&page).ok_or(SessionError::NotLoggedIn)
+    }
+
+    /// Submits a solution, returning the submission ID if it could be found.
+    pub fn submit(
+        &self,
+        id: &ProblemId,
+        program_type_id: u32,
+        source: &str,
+    ) -> Result<Option<u64>, SessionError> {
+        let url = self.url(&submit_path(id));
+        let page = self.get(&url)?;
+        if logged_in_handle(&page).is_none() {
+            return Err(SessionError::NotLoggedIn);
+        }
+        let csrf = csrf_token(&page)?;
+
+        let program_type_id = program_type_id.to_string();
+        let (problem_field, problem) = match id.kind {
+            ProblemKind::Acmsguru => ("submittedProblemCode", id.index.clone()),
+            _ => ("submittedProblemIndex", id.index.clone()),
+        };
+        let (final_url, page) = self.post(
+            &format!("{}?csrf_token={}", url, csrf),
+            &[
+                ("csrf_token", &csrf),
+                ("ftaa", &self.ftaa),
+                ("bfaa", &self.bfaa),
+                ("action", "submitSolutionFormSubmitted"),
+                (problem_field, &problem),
+                ("programTypeId", &program_type_id),
+                ("source", source),
+                ("tabSize", "4"),
+                ("sourceFile", ""),
+                ("_tta", "176"),
+            ],
+        )?;
+
+        // Success redirects to the "my submissions" page
+        if final_url.path() == url_path(&url) {
+            return Err(SessionError::Rejected(
+                form_error(&page).unwrap_or_else(|| "unknown reason".to_string()),
+            ));
+        }
+        Ok(latest_submission_id(&page))
+    }
+}
+
+fn url_path(url: &str) -> String {
+    Url::parse(url).map_or(String::new(), |u| u.path().to_string())
+}
+
+fn submit_path(id: &ProblemId) -> String {
+    match id.kind {
+        ProblemKind::Contest => format!("/contest/{}/submit", id.contest),
+        ProblemKind::Gym => format!("/gym/{}/submit", id.contest),
+        ProblemKind::Acmsguru => "/problemsets/acmsguru/submit".to_string(),
+    }
+}
+
+fn random_string(alphabet: &str, len: usize) -> String {
+    let chars: Vec<char> = alphabet.chars().collect();
+    (0..len)
+        .map(|_| chars[fastrand::usize(..chars.len())])
+        .collect()
+}
+
+fn select_attr(html: &str, selector: &str, attr: &str) -> Option<String> {
+    let doc = Html::parse_document(html);
+    let selector = Selector::parse(selector).expect("valid selector");
+    let value = doc.select(&selector).next()?.value().attr(attr)?;
+    Some(value.to_string())
+}
+
+fn csrf_token(html: &str) -> Result<String, SessionError> {
+    select_attr(html, "meta[name=X-Csrf-Token]", "content")
+        .or_else(|| select_attr(html, "input[name=csrf_token]", "value"))
+        .ok_or_else(|| SessionError::Parse("no CSRF token".to_string()))
+}
+
+/// The handle in the page header when logged in.
+fn logged_in_handle(html: &str) -> Option<String> {
+    let doc = Html::parse_document(html);
+    let selector =
+        Selector::parse("div.lang-chooser a[href^=\"/profile/\"]").expect("valid selector");
+    let link = doc.select(&selector).next()?;
+    let handle = link.value().attr("href")?.trim_start_matches("/profile/");
+    Some(handle.to_string())
+}
+
+/// The first error shown next to a form field.
+fn form_error(html: &str) -> Option<String> {
+    let doc = Html::parse_document(html);
+    let selector = Selector::parse("span.error").expect("valid selector");
+    doc.select(&selector)
+        .map(|e| e.text().collect::<String>().trim().to_string())
+        .find(|text| !text.is_empty())
+}
+
+fn latest_submission_id(html: &str) -> Option<u64> {
+    select_attr(html, "tr[data-submission-id]", "data-submission-id")?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pages() {
+        let page = r#"<html><head><meta name="X-Csrf-Token" content="abc123"/></head><body>
+            <div class="lang-chooser"><a href="/profile/tourist">tourist</a> | <a href="/x/logout">Logout</a></div>
+            <span class="error for__source">You have submitted exactly the same code before</span>
+            <table><tr data-submission-id="243000003"></tr><tr data-submission-id="243000002"></tr></table>
+            </body></html>"#;
+        assert_eq!(csrf_token(page).unwrap(), "abc123");
+        assert_eq!(logged_in_handle(page).as_deref(), Some("tourist"));
+        assert_eq!(
+            form_error(page).as_deref(),
+            Some("You have submitted exactly the same code before")
+        );
+        assert_eq!(latest_submission_id(page), Some(243000003));
+
+        let anonymous = r#"<div class="lang-chooser"><a href="/enter">Enter</a></div>
+            <input type="hidden" name="csrf_token" value="def456"/>"#;
+        assert_eq!(logged_in_handle(anonymous), None);
+        assert_eq!(csrf_token(anonymous).unwrap(), "def456");
+    }
+
+    #[test]
+    fn test_cookies_round_trip() {
+        let session = Session::new(
+            "https://codeforces.com",
+            Duration::from_secs(1),
+            Some("JSESSIONID=abc; 39ce7=xyz"),
+        )
+        .unwrap();
+        let cookies = session.cookies().unwrap();
+        assert!(cookies.contains("JSESSIONID=abc"));
+        assert!(cookies.contains("39ce7=xyz"));
+    }
+}
@@ -0,0 +1,85 @@
+//! Reusable code snippets stored as `snippets/{name}.{ext}` and inserted into
+//! solutions at the `cf:lib` marker comment from the templates.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Text that marks the insertion point in a solution, inside a comment.
+pub const MARKER: &str = "cf:lib";
+
+/// Available snippets by name, with the extensions each one exists in.
+pub fn list(dir: &Path) -> BTreeMap<String, Vec<String>> {
+    let mut snippets: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let (Some(stem), Some(ext)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+            snippets
+                .entry(stem.to_string())
+                .or_default()
+                .push(ext.to_string());
+        }
+    }
+    for exts in snippets.values_mut() {
+        exts.sort();
+    }
+    snippets
+}
+
+pub fn snippet_path(dir: &Path, name: &str, ext: &str) -> PathBuf {
+    dir.join(format!("{}.{}", name, ext))
+}
+
+/// Inserts `snippet` on the lines before the marker comment in `source`.
+pub fn insert(source: &str, snippet: &str) -> Result<String, String> {
+    if source.contains(snippet.trim()) {
+        return Err("Snippet is already present".to_string());
+    }
+
+    let mut out = String::with_capacity(source.len() + snippet.len() + 2);
+    let mut inserted = false;
+    for line in source.split_inclusive('\n') {
+        if !inserted && line.contains(MARKER) {
+            out.push_str(snippet.trim_end());
+            out.push_str("\n\n");
+            inserted = true;
+        }
+        out.push_str(line);
+    }
+
+    if inserted {
+        Ok(out)
+    } else {
+        Err(format!("No `{}` marker comment found", MARKER))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_before_marker() {
+        let source = "import sys\n\n# cf:lib\n\ndef solve():\n    pass\n";
+        let out = insert(source, "def f():\n    return 1\n").unwrap();
+        assert_eq!(
+            out,
+            "import sys\n\ndef f():\n    return 1\n\n# cf:lib\n\ndef solve():\n    pass\n"
+        );
+        assert!(insert(&out, "def f():\n    return 1\n").is_err());
+    }
+
+    #[test]
+    fn test_insert_without_marker() {
+        assert!(insert("int main() {}\n", "int f();").is_err());
+    }
+}
@@ -20,6 +20,8 @@ typedef pair<int, int> pii;
 #define fi first
 #define se second
 
+// cf:lib
+
 void solve() {
 }
 
@@ -1,5 +1,7 @@
 import Control.Monad (replicateM_)
 
+-- cf:lib
+
 solve :: IO ()
 solve = do
     return ()
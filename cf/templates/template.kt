@@ -0,0 +1,29 @@
+import java.io.BufferedReader
+import java.io.InputStreamReader
+import java.io.PrintWriter
+import java.util.StringTokenizer
+
+private val input = BufferedReader(InputStreamReader(System.`in`))
+private var tokens = StringTokenizer("")
+private val out = PrintWriter(System.out)
+
+private fun next(): String {
+    while (!tokens.hasMoreTokens()) tokens = StringTokenizer(input.readLine())
+    return tokens.nextToken()
+}
+
+private fun nextInt() = next().toInt()
+private fun nextLong() = next().toLong()
+
+// cf:lib
+
+fun solve() {
+}
+
+fun main() {
+    val t = nextInt()
+    repeat(t) {
+        solve()
+    }
+    out.flush()
+}
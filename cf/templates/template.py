@@ -3,5 +3,7 @@ __author__ = 'ius'
 from sys import stdin
 input = stdin.readline
 
+# cf:lib
+
 # Solution here
 pass
@@ -0,0 +1,19 @@
+use std::io::{self, BufWriter, Read, Write};
+
+// cf:lib
+
+fn solve(it: &mut impl Iterator<Item = i64>, out: &mut impl Write) {
+    let _ = (it, out);
+}
+
+fn main() {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).unwrap();
+    let mut it = input.split_ascii_whitespace().map(|x| x.parse::<i64>().unwrap());
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    let t = it.next().unwrap();
+    for _ in 0..t {
+        solve(&mut it, &mut out);
+    }
+}
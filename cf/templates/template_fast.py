@@ -13,6 +13,8 @@ def next_int() -> int:
     return int(next_token())
 
 
+# cf:lib
+
 def solve():
     # Solution here
     pass
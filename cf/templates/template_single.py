@@ -3,6 +3,8 @@ __author__ = 'ius'
 from sys import stdin
 input = stdin.readline
 
+# cf:lib
+
 def solve():
     # Solution here
     pass
@@ -0,0 +1,697 @@
+//! End-to-end tests running the `cf` binary against a local server that
+//! serves recorded Codeforces API and HTML responses from `tests/fixtures`.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+struct MockServer {
+    url: String,
+    /// Request URLs (path and query), in order
+    requests: Arc<Mutex<Vec<String>>>,
+    /// Bodies of POST requests, in order
+    posts: Arc<Mutex<Vec<String>>>,
+}
+
+/// The session cookie the mock site hands out on login.
+const SESSION_COOKIE: &str = "JSESSIONID=3C1E4F2A9B";
+
+struct Reply {
+    status: u16,
+    fixture: Option<&'static str>,
+    headers: Vec<(&'static str, String)>,
+}
+
+impl Reply {
+    fn fixture(status: u16, fixture: &'static str) -> Reply {
+        Reply {
+            status,
+            fixture: Some(fixture),
+            headers: Vec::new(),
+        }
+    }
+
+    fn redirect(location: &str) -> Reply {
+        Reply {
+            status: 302,
+            fixture: None,
+            headers: vec![("Location", location.to_string())],
+        }
+    }
+}
+
+impl MockServer {
+    fn start() -> MockServer {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("Failed to start mock server");
+        let url = format!("http://{}", server.server_addr().to_ip().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let posts = Arc::new(Mutex::new(Vec::new()));
+        let (log, post_log) = (Arc::clone(&requests), Arc::clone(&posts));
+        thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let url = request.url().to_string();
+                log.lock().unwrap().push(url.clone());
+                let logged_in = request
+                    .headers()
+                    .iter()
+                    .any(|h| h.field.equiv("Cookie") && h.value.as_str().contains(SESSION_COOKIE));
+                let reply = if *request.method() == tiny_http::Method::Post {
+                    let mut body = String::new();
+                    request.as_reader().read_to_string(&mut body).unwrap();
+                    post_log.lock().unwrap().push(body.clone());
+                    route_post(&url, &body)
+                } else {
+                    route(&url, logged_in)
+                };
+
+                let body = reply.fixture.map_or_else(String::new, |f| {
+                    fs::read_to_string(fixtures_dir().join(f)).expect("Missing fixture")
+                });
+                let content_type = if reply.fixture.is_some_and(|f| f.ends_with(".json")) {
+                    "application/json"
+                } else {
+                    "text/html"
+                };
+                let mut response = tiny_http::Response::from_string(body)
+                    .with_status_code(reply.status)
+                    .with_header(
+                        tiny_http::Header::from_bytes("Content-Type", content_type).unwrap(),
+                    );
+                for (name, value) in &reply.headers {
+                    response
+                        .add_header(tiny_http::Header::from_bytes(*name, value.as_str()).unwrap());
+                }
+                let _ = request.respond(response);
+            }
+        });
+        MockServer {
+            url,
+            requests,
+            posts,
+        }
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn posts(&self) -> Vec<String> {
+        self.posts.lock().unwrap().clone()
+    }
+}
+
+/// Reply to a GET request.
+fn route(url: &str, logged_in: bool) -> Reply {
+    let path = url.split('?').next().unwrap_or(url);
+    match path {
+        "/api/user.info" if url.contains("handles=nobody_xyz") => {
+            Reply::fixture(400, "user.info.failed.json")
+        }
+        "/api/user.info" => Reply::fixture(200, "user.info.json"),
+        "/api/user.status" | "/api/contest.status" => Reply::fixture(200, "user.status.json"),
+        "/api/contest.standings" if url.contains("apiSig=") => {
+            Reply::fixture(200, "contest.standings.json")
+        }
+        "/api/contest.standings" => Reply::fixture(400, "contest.standings.failed.json"),
+        "/contest/1900/problem/A" => Reply::fixture(200, "problem-1900A.html"),
+        "/enter" => Reply::fixture(200, "enter.html"),
+        "/" if logged_in => Reply::fixture(200, "home.html"),
+        "/contest/1900/submit" if logged_in => Reply::fixture(200, "submit-1900.html"),
+        "/contest/1900/submit" => Reply::redirect("/enter"),
+        "/contest/1900/my" if logged_in => Reply::fixture(200, "my-1900.html"),
+        _ => Reply {
+            status: 404,
+            fixture: None,
+            headers: Vec::new(),
+        },
+    }
+}
+
+/// Reply to a POST request with a form `body`.
+fn route_post(url: &str, body: &str) -> Reply {
+    let path = url.split('?').next().unwrap_or(url);
+    match path {
+        "/enter" if body.contains("password=secret") => {
+            let mut reply = Reply::redirect("/");
+            reply
+                .headers
+                .push(("Set-Cookie", format!("{}; Path=/", SESSION_COOKIE)));
+            reply
+        }
+        "/enter" => Reply::fixture(200, "enter.html"),
+        "/contest/1900/submit" if body.contains("already+submitted") => {
+            Reply::fixture(200, "submit-1900-rejected.html")
+        }
+        "/contest/1900/submit" => Reply::redirect("/contest/1900/my"),
+        _ => Reply {
+            status: 404,
+            fixture: None,
+            headers: Vec::new(),
+        },
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// A scratch directory with its own config and cache, and a mock server.
+struct Sandbox {
+    dir: TempDir,
+    server: MockServer,
+}
+
+impl Sandbox {
+    fn new() -> Sandbox {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("work")).unwrap();
+        Sandbox {
+            dir,
+            server: MockServer::start(),
+        }
+    }
+
+    fn work(&self) -> PathBuf {
+        self.dir.path().join("work")
+    }
+
+    fn config_file(&self) -> PathBuf {
+        self.dir.path().join("config/cf/config.json")
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_cf"));
+        cmd.args(args)
+            .current_dir(self.work())
+            .env("HOME", self.dir.path())
+            .env("XDG_CONFIG_HOME", self.dir.path().join("config"))
+            .env("XDG_CACHE_HOME", self.dir.path().join("cache"))
+            .env("CF_BASE_URL", &self.server.url)
+            .env("CF_BROWSER", "true")
+            .env("NO_COLOR", "1");
+        for var in [
+            "CF_HANDLE",
+            "CF_PROFILE",
+            "CF_LANG",
+            "CF_LAYOUT",
+            "CF_CHECKER",
+        ] {
+            cmd.env_remove(var);
+        }
+        cmd
+    }
+
+    fn run(&self, args: &[&str]) -> Output {
+        self.run_with_input(args, "")
+    }
+
+    fn run_with_input(&self, args: &[&str], input: &str) -> Output {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to run cf");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+#[test]
+fn test_login_saves_verified_handle() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run_with_input(&["login"], "tourist\n");
+    assert!(stdout(&output).contains("Handle verified"), "{:?}", output);
+
+    let config = fs::read_to_string(sandbox.config_file()).unwrap();
+    assert!(config.contains("\"handle\": \"tourist\""), "{}", config);
+    assert_eq!(
+        sandbox.server.requests(),
+        ["/api/user.info?handles=tourist"]
+    );
+}
+
+#[test]
+fn test_login_with_profile() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run_with_input(&["login", "--profile", "alice"], "tourist\n");
+    assert!(stdout(&output).contains("profile alice"), "{:?}", output);
+
+    let handle = stdout(&sandbox.run(&["--profile", "alice", "config", "get", "handle"]));
+    assert_eq!(handle.trim(), "tourist");
+    let output = sandbox.run(&["config", "get", "handle"]);
+    assert!(!output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn test_login_rejects_unknown_handle() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run_with_input(&["login"], "nobody_xyz\n");
+    assert!(stderr(&output).contains("Handle not found"), "{:?}", output);
+    assert!(!sandbox.config_file().exists());
+}
+
+#[test]
+fn test_watch_lists_submissions() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run(&["watch"]);
+    assert!(stderr(&output).contains("Not logged in"));
+
+    let output = sandbox
+        .command(&["watch"])
+        .env("CF_HANDLE", "tourist")
+        .output()
+        .unwrap();
+    let out = stdout(&output);
+    assert!(out.contains("1900B"), "{}", out);
+    assert!(out.contains("WA on test 5"), "{}", out);
+    assert!(out.contains("243000001"), "{}", out);
+    assert!(out.contains("124 ms"), "{}", out);
+    assert!(out.contains("2300 KB"), "{}", out);
+    assert!(sandbox.server.requests()[0].starts_with("/api/user.status?handle=tourist"));
+
+    let watch = |args: &[&str]| {
+        let output = sandbox
+            .command(args)
+            .env("CF_HANDLE", "tourist")
+            .output()
+            .unwrap();
+        stdout(&output)
+    };
+    let out = watch(&["watch", "--limit", "1"]);
+    assert!(out.contains("243000003"), "{}", out);
+    assert!(!out.contains("243000002"), "{}", out);
+    assert!(out.contains("showing the latest 1"), "{}", out);
+    // The fixture's submissions are from 2023
+    let out = watch(&["watch", "--since", "2d"]);
+    assert!(out.contains("No matching submissions"), "{}", out);
+    let out = watch(&["watch", "--verdict", "WA,TLE", "--rating", "800.."]);
+    assert!(out.contains("243000002"), "{}", out);
+    assert!(!out.contains("243000001"), "{}", out);
+    let out = watch(&["watch", "1900A", "--lang", "py", "--tag", "greedy"]);
+    assert!(out.contains("243000001"), "{}", out);
+    assert!(!out.contains("243000002"), "{}", out);
+}
+
+#[test]
+fn test_eg_downloads_samples() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run(&["eg", "1900A"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let samples = sandbox.work().join("samples/1900A");
+    assert_eq!(
+        fs::read_to_string(samples.join("in1.txt")).unwrap(),
+        "5\n3\n...\n7\n##....#\n7\n..#.#..\n4\n####\n10\n#...#..#.#\n"
+    );
+    assert_eq!(
+        fs::read_to_string(samples.join("ans1.txt")).unwrap(),
+        "2\n2\n5\n0\n2\n"
+    );
+    assert!(!samples.join("in2.txt").exists());
+}
+
+#[test]
+fn test_eg_falls_back_to_empty_files() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run(&["eg", "1900B", "2"]);
+    let out = stdout(&output);
+    assert!(
+        out.contains(&format!(
+            "Opening: {}/contest/1900/problem/B",
+            sandbox.server.url
+        )),
+        "{}",
+        out
+    );
+    let samples = sandbox.work().join("samples/1900B");
+    assert_eq!(fs::read_to_string(samples.join("in2.txt")).unwrap(), "");
+}
+
+#[test]
+fn test_submit_opens_submit_page() {
+    let sandbox = Sandbox::new();
+    let solution = sandbox.work().join("solutions/A-set/1900A.py");
+    fs::create_dir_all(solution.parent().unwrap()).unwrap();
+    fs::write(&solution, "print(1)\n").unwrap();
+
+    let output = sandbox.run(&["submit", "1900A"]);
+    let out = stdout(&output);
+    assert!(
+        out.contains(&format!(
+            "Opening: {}/contest/1900/submit/A",
+            sandbox.server.url
+        )),
+        "{}",
+        out
+    );
+    assert!(out.contains("1900A.py"), "{}", out);
+}
+
+#[test]
+fn test_offline_serves_cached_pages() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run(&["--offline", "eg", "1900A"]);
+    assert!(
+        stderr(&output).contains("Not cached (offline)"),
+        "{:?}",
+        output
+    );
+    assert!(sandbox.server.requests().is_empty());
+    fs::remove_dir_all(sandbox.work().join("samples")).unwrap();
+
+    sandbox.run(&["eg", "1900A"]);
+    fs::remove_dir_all(sandbox.work().join("samples")).unwrap();
+    sandbox.run(&["--offline", "eg", "1900A"]);
+    let input = sandbox.work().join("samples/1900A/in1.txt");
+    assert!(fs::read_to_string(input).unwrap().starts_with("5\n3\n"));
+    assert_eq!(sandbox.server.requests().len(), 1);
+
+    let stats = stdout(&sandbox.run(&["cache", "stats"]));
+    assert!(stats.contains("page"), "{}", stats);
+    let cleared = stdout(&sandbox.run(&["cache", "clear"]));
+    assert!(cleared.contains("Removed 1"), "{}", cleared);
+}
+
+#[test]
+fn test_login_and_submit_over_http() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run_with_input(&["login"], "tourist\nwrong\n");
+    assert!(
+        stderr(&output).contains("Wrong handle or password"),
+        "{:?}",
+        output
+    );
+
+    let output = sandbox.run_with_input(&["login"], "tourist\nsecret\n");
+    assert!(
+        stdout(&output).contains("Logged in as tourist"),
+        "{:?}",
+        output
+    );
+    let config = fs::read_to_string(sandbox.config_file()).unwrap();
+    assert!(config.contains(SESSION_COOKIE), "{}", config);
+    assert!(!config.contains("secret"), "{}", config);
+
+    let solution = sandbox.work().join("solutions/A-set/1900A.py");
+    fs::create_dir_all(solution.parent().unwrap()).unwrap();
+    fs::write(&solution, "print(2)\n").unwrap();
+    let output = sandbox.run(&["submit", "1900A"]);
+    let out = stdout(&output);
+    assert!(out.contains("Submitted: 243000003"), "{:?}", output);
+    assert!(!out.contains("Opening"), "{}", out);
+    assert!(out.contains("Accepted, 124 ms, 2300 KB"), "{}", out);
+    assert!(output.status.success());
+
+    let form = sandbox.server.posts().pop().unwrap();
+    assert!(form.contains("submittedProblemIndex=A"), "{}", form);
+    assert!(form.contains("programTypeId=31"), "{}", form);
+    assert!(form.contains("source=print%282%29"), "{}", form);
+
+    let output = sandbox.run(&["submit", "1900A", "--lang", "pypy3"]);
+    assert!(stdout(&output).contains("with PyPy 3.10"), "{:?}", output);
+    assert!(sandbox
+        .server
+        .posts()
+        .pop()
+        .unwrap()
+        .contains("programTypeId=70"));
+    sandbox.run(&["config", "set", "compiler.py", "41"]);
+    sandbox.run(&["submit", "1900A"]);
+    assert!(sandbox
+        .server
+        .posts()
+        .pop()
+        .unwrap()
+        .contains("programTypeId=41"));
+
+    // Rejected submissions are reported, then the browser is opened
+    fs::write(&solution, "# already submitted\n").unwrap();
+    let output = sandbox.run(&["submit", "1900A"]);
+    assert!(
+        stderr(&output).contains("You have submitted exactly the same code before"),
+        "{:?}",
+        output
+    );
+    assert!(stdout(&output).contains("Opening"));
+}
+
+#[test]
+fn test_submit_checks_solution_first() {
+    let sandbox = Sandbox::new();
+    let samples = sandbox.work().join("samples/1900A");
+    fs::create_dir_all(&samples).unwrap();
+    fs::write(samples.join("in1.txt"), "1\n").unwrap();
+    fs::write(samples.join("ans1.txt"), "2\n").unwrap();
+    let solution = sandbox.work().join("solutions/A-set/1900A.py");
+    fs::create_dir_all(solution.parent().unwrap()).unwrap();
+
+    fs::write(
+        &solution,
+        "import sys\nprint(1, file=sys.stderr)\nprint(1)\n",
+    )
+    .unwrap();
+    let output = sandbox.run(&["submit", "1900A"]);
+    let err = stderr(&output);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(err.contains("debug output"), "{}", err);
+    assert!(err.contains("Samples failed"), "{}", err);
+    assert!(!stdout(&output).contains("Opening"));
+
+    let output = sandbox.run(&["submit", "1900A", "--force"]);
+    assert!(stdout(&output).contains("Opening"), "{:?}", output);
+
+    fs::write(&solution, "print(2)\n").unwrap();
+    let output = sandbox.run(&["submit", "1900A"]);
+    assert!(stdout(&output).contains("Opening"), "{:?}", output);
+
+    // A C++ file when Python is the configured language
+    fs::remove_file(&solution).unwrap();
+    fs::write(solution.with_extension("cpp"), "int main() {}\n").unwrap();
+    let output = sandbox.run(&["submit", "1900A"]);
+    assert!(
+        stderr(&output).contains("No py solution for 1900A"),
+        "{:?}",
+        output
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_watch_follow_keeps_polling() {
+    let sandbox = Sandbox::new();
+    let mut child = sandbox
+        .command(&["watch", "--follow", "--interval", "2"])
+        .env("CF_HANDLE", "tourist")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(3000));
+    child.kill().unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    // Unchanged submissions are printed once, not on every refresh
+    let out = stdout(&output);
+    assert_eq!(out.matches("243000003").count(), 1, "{}", out);
+    let polls = sandbox
+        .server
+        .requests()
+        .iter()
+        .filter(|r| r.starts_with("/api/user.status"))
+        .count();
+    assert!(polls >= 2, "{:?}", sandbox.server.requests());
+}
+
+#[test]
+fn test_json_output() {
+    let sandbox = Sandbox::new();
+    let samples = sandbox.work().join("samples/1900A");
+    fs::create_dir_all(&samples).unwrap();
+    fs::write(samples.join("in1.txt"), "1\n").unwrap();
+    fs::write(samples.join("ans1.txt"), "2\n").unwrap();
+    fs::write(samples.join("in2.txt"), "5\n").unwrap();
+    fs::write(samples.join("ans2.txt"), "7\n").unwrap();
+    let solution = sandbox.work().join("solutions/A-set/1900A.py");
+    fs::create_dir_all(solution.parent().unwrap()).unwrap();
+    fs::write(&solution, "print(int(input()) + 1)\n").unwrap();
+
+    let output = sandbox.run(&["--json", "test", "1900A"]);
+    let result: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(result["passed"], 1);
+    assert_eq!(result["failed"], 1);
+    assert_eq!(result["tests"][0]["verdict"], "passed");
+    assert_eq!(result["tests"][1]["verdict"], "failed");
+    assert_eq!(result["tests"][1]["output"], "6\n");
+
+    let output = sandbox.run(&["list", "--json"]);
+    let result: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(result["solutions"]["A-set"], 1);
+
+    let output = sandbox
+        .command(&["--json", "watch", "--verdict", "OK"])
+        .env("CF_HANDLE", "tourist")
+        .output()
+        .unwrap();
+    let result: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(result.as_array().unwrap().len(), 2);
+    assert_eq!(result[0]["id"], 243000003);
+    assert_eq!(result[0]["problem"]["index"], "A");
+}
+
+#[test]
+fn test_all_runs_every_solution() {
+    let sandbox = Sandbox::new();
+    for (name, set, solution) in [
+        ("1900A", "A", "print(int(input()) + 1)\n"),
+        ("1900B", "B", "print(0)\n"),
+    ] {
+        let samples = sandbox.work().join("samples").join(name);
+        fs::create_dir_all(&samples).unwrap();
+        fs::write(samples.join("in1.txt"), "1\n").unwrap();
+        fs::write(samples.join("ans1.txt"), "2\n").unwrap();
+        let path = sandbox
+            .work()
+            .join(format!("solutions/{}-set/{}.py", set, name));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, solution).unwrap();
+    }
+    fs::write(
+        sandbox.work().join("solutions/A-set/1900A.cpp"),
+        "#include <iostream>\nint main() { int n; std::cin >> n; std::cout << n * 2; }\n",
+    )
+    .unwrap();
+    let unsampled = sandbox.work().join("solutions/C-set/1900C.py");
+    fs::create_dir_all(unsampled.parent().unwrap()).unwrap();
+    fs::write(&unsampled, "print(1)\n").unwrap();
+
+    let output = sandbox.run(&["test", "--all", "--report", "junit.xml"]);
+    assert_eq!(output.status.code(), Some(3));
+    let out = stdout(&output);
+    assert!(out.contains("Problem    py    cpp"), "{}", out);
+    assert!(out.contains("1900A      pass  pass"), "{}", out);
+    assert!(out.contains("1900B      FAIL  -"), "{}", out);
+    assert!(out.contains("Solutions: 2 passed, 1 failed"), "{}", out);
+    assert!(
+        out.contains("Skipped 1 solutions without samples"),
+        "{}",
+        out
+    );
+
+    let xml = fs::read_to_string(sandbox.work().join("junit.xml")).unwrap();
+    assert!(xml.contains("<testsuite name=\"1900A.py\" tests=\"1\" failures=\"0\""));
+    assert!(xml.contains("<testsuite name=\"1900B.py\" tests=\"1\" failures=\"1\""));
+    assert!(xml.contains("-2\n+0\n"), "{}", xml);
+
+    let out = stdout(&sandbox.run(&["test", "--all", "--lang", "cpp"]));
+    assert!(out.contains("Solutions: 1 passed, 0 failed"), "{}", out);
+    assert_eq!(sandbox.run(&["test"]).status.code(), Some(2));
+}
+
+#[test]
+fn test_exit_codes() {
+    let sandbox = Sandbox::new();
+    let samples = sandbox.work().join("samples/1900A");
+    fs::create_dir_all(&samples).unwrap();
+    fs::write(samples.join("in1.txt"), "1\n").unwrap();
+    fs::write(samples.join("ans1.txt"), "2\n").unwrap();
+    let solution = sandbox.work().join("solutions/A-set/1900A.py");
+    fs::create_dir_all(solution.parent().unwrap()).unwrap();
+
+    fs::write(&solution, "print(2)\n").unwrap();
+    assert_eq!(sandbox.run(&["test", "1900A"]).status.code(), Some(0));
+    let output = sandbox.run(&["test", "1900A", "--report", "missing/junit.xml"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Failed to write"), "{:?}", output);
+    fs::write(&solution, "print(3)\n").unwrap();
+    let output = sandbox.run(&["test", "1900A"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr(&output).is_empty(), "{:?}", output);
+
+    fs::write(solution.with_extension("cpp"), "int main() {\n").unwrap();
+    let output = sandbox.run(&["test", "1900A", "--lang", "cpp"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(
+        stderr(&output).contains("Compilation failed"),
+        "{:?}",
+        output
+    );
+
+    let output = sandbox.run(&["test", "1900B"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("No solution file found for 1900B"));
+
+    // A file in the way of the samples directory
+    fs::write(sandbox.work().join("samples/1900B"), "").unwrap();
+    let output = sandbox.run(&["eg", "1900B"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Failed to create"), "{:?}", output);
+
+    assert_eq!(sandbox.run(&["watch"]).status.code(), Some(6));
+    let output = sandbox.run(&["stats", "nobody_xyz"]);
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+}
+
+#[test]
+fn test_standings_of_private_gym() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run(&["standings", "100001"]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(
+        stderr(&output).contains("You are not allowed"),
+        "{:?}",
+        output
+    );
+
+    fs::create_dir_all(sandbox.config_file().parent().unwrap()).unwrap();
+    fs::write(
+        sandbox.config_file(),
+        r#"{"handle": "tourist", "api_key": "k3y", "api_secret": "s3cret"}"#,
+    )
+    .unwrap();
+    let output = sandbox.run(&["standings", "100001", "-n", "2"]);
+    let out = stdout(&output);
+    assert!(out.contains("Private Training 1"), "{:?}", output);
+    assert!(out.contains("    1  tourist"), "{}", out);
+    assert!(out.contains("Team Rocket"), "{}", out);
+    let request = sandbox.server.requests().pop().unwrap();
+    assert!(request.contains("apiKey=k3y"), "{}", request);
+    assert!(request.contains("count=2"), "{}", request);
+}
+
+#[test]
+fn test_migrate_lists_unmatched_files() {
+    let sandbox = Sandbox::new();
+    let solutions = sandbox.work().join("solutions");
+    fs::create_dir_all(solutions.join("A-set")).unwrap();
+    fs::write(solutions.join("A-set/1900A.py"), "print(1)\n").unwrap();
+    fs::write(solutions.join("scratch.py"), "print(2)\n").unwrap();
+
+    let layout = "contests/{contest}/{letter}.{ext}";
+    let output = sandbox.run(&["migrate", layout, "--dry-run"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("scratch.py"), "{:?}", output);
+
+    let output = sandbox.run(&["migrate", layout, "--force"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(solutions.join("contests/1900/A.py").exists());
+    assert!(solutions.join("scratch.py").exists());
+}
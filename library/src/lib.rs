@@ -0,0 +1,5 @@
+//! Reusable snippets for Rust solutions. `cf test` and `cf submit` inline the
+//! modules a solution uses, so keep each module self-contained apart from
+//! `crate::` references to other modules here.
+
+pub mod math;
@@ -0,0 +1,18 @@
+struct DSU {
+    vi parent, sz;
+    DSU(int n) : parent(n), sz(n, 1) {
+        REP(i, n) parent[i] = i;
+    }
+    int find(int x) {
+        while (parent[x] != x) x = parent[x] = parent[parent[x]];
+        return x;
+    }
+    bool unite(int a, int b) {
+        a = find(a), b = find(b);
+        if (a == b) return false;
+        if (sz[a] < sz[b]) swap(a, b);
+        parent[b] = a;
+        sz[a] += sz[b];
+        return true;
+    }
+};
@@ -0,0 +1,23 @@
+// Point update, range query over [l, r) with an associative op.
+template <typename T, typename Op>
+struct SegTree {
+    int n;
+    T e;
+    Op op;
+    vector<T> t;
+    SegTree(const vector<T>& data, T e, Op op) : n(data.size()), e(e), op(op), t(2 * n, e) {
+        REP(i, n) t[n + i] = data[i];
+        for (int i = n - 1; i > 0; i--) t[i] = op(t[2 * i], t[2 * i + 1]);
+    }
+    void update(int i, T value) {
+        for (t[i += n] = value; i > 1; i >>= 1) t[i >> 1] = op(t[i & ~1], t[i | 1]);
+    }
+    T query(int l, int r) const {
+        T res_l = e, res_r = e;
+        for (l += n, r += n; l < r; l >>= 1, r >>= 1) {
+            if (l & 1) res_l = op(res_l, t[l++]);
+            if (r & 1) res_r = op(t[--r], res_r);
+        }
+        return op(res_l, res_r);
+    }
+};
@@ -0,0 +1,31 @@
+class SegTree:
+    """Point update, range query over [l, r) with an associative op."""
+
+    def __init__(self, data, op=min, e=float("inf")):
+        self.n = len(data)
+        self.op, self.e = op, e
+        self.t = [e] * self.n + list(data)
+        for i in range(self.n - 1, 0, -1):
+            self.t[i] = op(self.t[2 * i], self.t[2 * i + 1])
+
+    def update(self, i, value):
+        i += self.n
+        self.t[i] = value
+        while i > 1:
+            i //= 2
+            self.t[i] = self.op(self.t[2 * i], self.t[2 * i + 1])
+
+    def query(self, l, r):
+        res_l, res_r = self.e, self.e
+        l += self.n
+        r += self.n
+        while l < r:
+            if l & 1:
+                res_l = self.op(res_l, self.t[l])
+                l += 1
+            if r & 1:
+                r -= 1
+                res_r = self.op(self.t[r], res_r)
+            l //= 2
+            r //= 2
+        return self.op(res_l, res_r)